@@ -0,0 +1,255 @@
+extern crate serde_json;
+
+use chrono::{DateTime, Utc};
+use failure::{Error, ResultExt};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use judgement::Violation;
+use options::AuditOptions;
+
+/// A PR that was approved even though rules found problems with it, either because it was force
+/// approved or because override labels waived the problems.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub time: DateTime<Utc>,
+    pub repository: String,
+    pub number: u64,
+    pub sha: String,
+    /// Description of the approval, as posted in the status.
+    pub reason: String,
+    /// Labels that approved the PR.
+    pub labels: Vec<String>,
+    /// Login of the user that applied the labels, when known.
+    pub actor: Option<String>,
+    /// Problems that were found but did not fail the PR.
+    pub waived: Vec<Violation>,
+}
+
+#[derive(Debug, Fail)]
+pub enum AuditLogError {
+    #[fail(display = "Could not access audit log")]
+    Io(#[cause] io::Error),
+
+    #[fail(display = "Could not serialize audit record")]
+    Serialize(#[cause] serde_json::Error),
+
+    #[fail(display = "Invalid audit record on line {}", _0)]
+    InvalidRecord(usize, #[cause] serde_json::Error),
+}
+
+/// Append-only log of audit records, stored as one JSON object per line.
+#[derive(Debug)]
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path) -> Result<AuditLog, AuditLogError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn append(&self, record: &AuditRecord) -> Result<(), AuditLogError> {
+        let mut line = serde_json::to_vec(record).map_err(AuditLogError::Serialize)?;
+        line.push(b'\n');
+
+        // Write the whole line at once so concurrent webhooks don't interleave their records.
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Which records to return when reading the audit log. Unset fields match every record.
+#[derive(Debug, Default)]
+pub struct AuditQuery {
+    pub repository: Option<String>,
+    pub number: Option<u64>,
+    pub actor: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.repository
+            .as_ref()
+            .map_or(true, |repository| repository.eq_ignore_ascii_case(&record.repository))
+            && self.number.map_or(true, |number| number == record.number)
+            && self.actor.as_ref().map_or(true, |actor| {
+                record
+                    .actor
+                    .as_ref()
+                    .map_or(false, |login| login.eq_ignore_ascii_case(actor))
+            })
+            && self.since.map_or(true, |since| record.time >= since)
+    }
+}
+
+/// Read the records in the audit log that match the query, oldest first.
+pub fn read(path: &Path, query: &AuditQuery) -> Result<Vec<AuditRecord>, AuditLogError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: AuditRecord = serde_json::from_str(&line)
+            .map_err(|error| AuditLogError::InvalidRecord(index + 1, error))?;
+        if query.matches(&record) {
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
+
+/// Print the records in the audit log that match the options.
+pub fn run(options: &AuditOptions, path: Option<&Path>) -> Result<(), Error> {
+    let path = match path {
+        Some(path) => path,
+        None => bail!("No audit log is configured. Set it with --audit-log."),
+    };
+    let query = AuditQuery {
+        repository: options.repository.clone(),
+        number: options.number,
+        actor: options.actor.clone(),
+        since: options.since,
+    };
+
+    for record in read(path, &query).context("Could not read audit log")? {
+        if options.json {
+            println!("{}", serde_json::to_string(&record)?);
+            continue;
+        }
+
+        println!(
+            "{} {}#{} {} by {}: {}",
+            record.time.to_rfc3339(),
+            record.repository,
+            record.number,
+            &record.sha[..record.sha.len().min(7)],
+            record
+                .actor
+                .as_ref()
+                .map_or_else(|| String::from("unknown"), |login| format!("@{}", login)),
+            record.reason,
+        );
+        for violation in &record.waived {
+            println!("  {}: {}", violation.rule, violation.message);
+        }
+    }
+
+    Ok(())
+}
+
+impl From<io::Error> for AuditLogError {
+    fn from(error: io::Error) -> AuditLogError {
+        AuditLogError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn record(repository: &str, number: u64, actor: Option<&str>) -> AuditRecord {
+        AuditRecord {
+            time: "2019-02-01T12:00:00Z".parse().unwrap(),
+            repository: repository.into(),
+            number,
+            sha: String::from("f98bf92fcba485635ec3e796c029258e32d18e63"),
+            reason: String::from("Approved with overrides: prgnome: allow-fixup"),
+            labels: vec![String::from("prgnome: allow-fixup")],
+            actor: actor.map(String::from),
+            waived: vec![Violation {
+                rule: String::from("forbidden_intros"),
+                message: String::from("Rebase away \"fixup! Add feature\""),
+                hint: String::from("Squash it."),
+            }],
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "prgnome-audit-{}-{}.jsonl",
+            name,
+            ::std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn it_appends_and_reads_records() {
+        let path = temp_path("append");
+        {
+            let log = AuditLog::open(&path).unwrap();
+            log.append(&record("example/repo", 1, Some("octocat"))).unwrap();
+        }
+        {
+            let log = AuditLog::open(&path).unwrap();
+            log.append(&record("example/repo", 2, None)).unwrap();
+        }
+
+        let records = read(&path, &AuditQuery::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                record("example/repo", 1, Some("octocat")),
+                record("example/repo", 2, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_filters_records() {
+        let query = AuditQuery {
+            repository: Some(String::from("Example/Repo")),
+            actor: Some(String::from("OctoCat")),
+            ..Default::default()
+        };
+        assert!(query.matches(&record("example/repo", 1, Some("octocat"))));
+        assert!(!query.matches(&record("example/repo", 1, None)));
+        assert!(!query.matches(&record("example/other", 1, Some("octocat"))));
+
+        let query = AuditQuery {
+            number: Some(2),
+            since: Some("2019-02-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(query.matches(&record("example/repo", 2, None)));
+        assert!(!query.matches(&record("example/repo", 1, None)));
+
+        let query = AuditQuery {
+            since: Some("2019-02-02T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(!query.matches(&record("example/repo", 2, None)));
+    }
+
+    #[test]
+    fn it_reports_the_line_of_invalid_records() {
+        let path = temp_path("invalid");
+        let valid = serde_json::to_string(&record("example/repo", 1, None)).unwrap();
+        fs::write(&path, format!("{}\n\n{{\"nope\": true}}\n", valid)).unwrap();
+
+        let error = read(&path, &AuditQuery::default()).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.to_string(), "Invalid audit record on line 3");
+    }
+}
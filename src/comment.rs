@@ -0,0 +1,256 @@
+use config::{Config, OnSuccess};
+use event::PullRequest;
+use github_api::{ApiError, Client as GithubClient, IssueComment};
+use judgement::{Judgement, Violation};
+
+const COLLAPSED_SUMMARY: &str = "<summary>All problems found by prgnome have been fixed.</summary>";
+
+/// Render the comment explaining a judgement, or `None` if there is nothing to explain.
+///
+/// The comment starts with a marker that contains the status context, so every deployment only
+/// touches its own comment.
+pub fn render(judgement: &Judgement, context: &str, pr: &PullRequest) -> Option<String> {
+    let violations = match judgement {
+        Judgement::NotApproved { violations } => violations,
+        Judgement::Approved | Judgement::ForceApproved(_) | Judgement::Overridden { .. } => {
+            return None
+        }
+    };
+
+    let base_branch = pr.base.ref_name.as_ref().map_or("main", String::as_str);
+
+    Some(format!(
+        "{marker}\n\
+         **This PR is not ready to be merged.** Fix {count} before merging:\n\
+         \n\
+         {problems}\n\
+         <details>\n\
+         <summary>How to rebase</summary>\n\
+         \n\
+         Most problems with commits are fixed with an interactive rebase onto the base branch. \
+         Commits starting with `fixup!` or `squash!` are squashed into the commits they amend \
+         automatically:\n\
+         \n\
+         ```sh\n\
+         git fetch origin\n\
+         git rebase -i --autosquash origin/{base_branch}\n\
+         git push --force-with-lease\n\
+         ```\n\
+         </details>\n\
+         \n\
+         This comment is updated whenever the PR changes.\n",
+        marker = marker(context),
+        count = if violations.len() == 1 {
+            String::from("this problem")
+        } else {
+            format!("these {} problems", violations.len())
+        },
+        problems = problems(violations),
+        base_branch = base_branch,
+    ))
+}
+
+/// Render a reply to `/prgnome explain`, explaining the judgement whatever it is.
+pub fn explain(judgement: &Judgement) -> String {
+    match judgement {
+        Judgement::Approved => String::from("prgnome found no problems with this PR."),
+        Judgement::ForceApproved(reason) => format!(
+            "prgnome approved this PR without checking it: {}.",
+            reason
+        ),
+        Judgement::Overridden { labels, waived } => format!(
+            "prgnome approved this PR, but only because {} waived {}:\n\n{}",
+            labels
+                .iter()
+                .map(|label| format!("`{}`", label))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if waived.len() == 1 {
+                String::from("this problem")
+            } else {
+                format!("these {} problems", waived.len())
+            },
+            problems(waived)
+        ),
+        Judgement::NotApproved { violations } => format!(
+            "prgnome found {} with this PR:\n\n{}",
+            if violations.len() == 1 {
+                String::from("a problem")
+            } else {
+                format!("{} problems", violations.len())
+            },
+            problems(violations)
+        ),
+    }
+}
+
+fn problems(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .enumerate()
+        .map(|(index, violation)| {
+            format!(
+                "{}. **{}**\n   {}\n",
+                index + 1,
+                violation.message,
+                violation.hint
+            )
+        }).collect()
+}
+
+/// Create, update, or remove the comment on a PR so that it explains the current judgement.
+/// `comments` are the comments on the PR, as listed before judging it.
+pub fn sync(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    pr: &PullRequest,
+    comments: &[IssueComment],
+    config: &Config,
+    judgement: &Judgement,
+) -> Result<(), ApiError> {
+    let context = &config.status.context;
+    let marker = marker(context);
+    let existing = comments
+        .iter()
+        .find(|comment| comment.user.user_type == "Bot" && comment.body.starts_with(&marker));
+
+    match (render(judgement, context, pr), existing) {
+        (Some(body), Some(comment)) => {
+            if normalize_newlines(&comment.body) != body {
+                debug!("Updating comment {} on PR #{}", comment.id, pr.number);
+                api_client.update_issue_comment(auth_token, repo_url, comment.id, &body)?;
+            }
+        }
+        (Some(body), None) => {
+            debug!("Commenting on PR #{}", pr.number);
+            api_client.create_issue_comment(auth_token, repo_url, pr.number, &body)?;
+        }
+        (None, Some(comment)) => match config.comments.on_success {
+            OnSuccess::Delete => {
+                debug!("Deleting comment {} on PR #{}", comment.id, pr.number);
+                api_client.delete_issue_comment(auth_token, repo_url, comment.id)?;
+            }
+            OnSuccess::Collapse => {
+                if !comment.body.contains(COLLAPSED_SUMMARY) {
+                    debug!("Collapsing comment {} on PR #{}", comment.id, pr.number);
+                    let body = collapse(&normalize_newlines(&comment.body), &marker);
+                    api_client.update_issue_comment(auth_token, repo_url, comment.id, &body)?;
+                }
+            }
+        },
+        (None, None) => {}
+    }
+
+    Ok(())
+}
+
+fn marker(context: &str) -> String {
+    format!("<!-- prgnome: {} -->", context)
+}
+
+fn collapse(body: &str, marker: &str) -> String {
+    format!(
+        "{}\n<details>\n{}\n\n{}\n</details>\n",
+        marker,
+        COLLAPSED_SUMMARY,
+        body.trim_start_matches(marker).trim()
+    )
+}
+
+fn normalize_newlines(body: &str) -> String {
+    body.replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event::pr_event;
+
+    #[test]
+    fn it_does_not_comment_on_approved_prs() {
+        let pr_event = pr_event("webhook_pr_labeled.json");
+        let pr = pr_event.pull_request().unwrap();
+
+        assert_eq!(render(&Judgement::Approved, "prgnome", pr), None);
+        assert_eq!(
+            render(&Judgement::ForceApproved(String::from("Yes")), "prgnome", pr),
+            None
+        );
+    }
+
+    #[test]
+    fn it_explains_every_violation() {
+        let pr_event = pr_event("webhook_pr_labeled.json");
+        let pr = pr_event.pull_request().unwrap();
+        let judgement = Judgement::NotApproved {
+            violations: vec![
+                Violation {
+                    rule: String::from("forbidden_intros"),
+                    message: String::from("Rebase away \"fixup! Stuff\""),
+                    hint: String::from("Squash it."),
+                },
+                Violation {
+                    rule: String::from("forbidden_labels"),
+                    message: String::from("Remove the \"wip\" label"),
+                    hint: String::from("Remove it."),
+                },
+            ],
+        };
+
+        let body = render(&judgement, "acme/prgnome", pr).unwrap();
+        assert!(body.starts_with("<!-- prgnome: acme/prgnome -->\n"));
+        assert!(body.contains("Fix these 2 problems"));
+        assert!(body.contains("1. **Rebase away \"fixup! Stuff\"**\n   Squash it.\n"));
+        assert!(body.contains("2. **Remove the \"wip\" label**\n   Remove it.\n"));
+        assert!(body.contains("git rebase -i --autosquash origin/master\n"));
+    }
+
+    #[test]
+    fn it_explains_every_kind_of_judgement() {
+        let violation = Violation {
+            rule: String::from("max_commits"),
+            message: String::from("Squash the 12 commits into at most 10"),
+            hint: String::from("Squash some."),
+        };
+
+        assert_eq!(
+            explain(&Judgement::Approved),
+            "prgnome found no problems with this PR."
+        );
+        assert_eq!(
+            explain(&Judgement::ForceApproved(String::from("Tagged with hotfix"))),
+            "prgnome approved this PR without checking it: Tagged with hotfix."
+        );
+        assert_eq!(
+            explain(&Judgement::Overridden {
+                labels: vec![String::from("/prgnome ignore max_commits")],
+                waived: vec![violation.clone()],
+            }),
+            "prgnome approved this PR, but only because `/prgnome ignore max_commits` waived this \
+             problem:\n\n1. **Squash the 12 commits into at most 10**\n   Squash some.\n"
+        );
+        assert_eq!(
+            explain(&Judgement::NotApproved {
+                violations: vec![violation.clone(), violation],
+            }),
+            "prgnome found 2 problems with this PR:\n\n\
+             1. **Squash the 12 commits into at most 10**\n   Squash some.\n\
+             2. **Squash the 12 commits into at most 10**\n   Squash some.\n"
+        );
+    }
+
+    #[test]
+    fn it_collapses_old_explanations() {
+        let marker = marker("prgnome");
+        let collapsed = collapse(&format!("{}\nOld explanation\n", marker), &marker);
+
+        assert_eq!(
+            collapsed,
+            format!(
+                "{}\n<details>\n{}\n\nOld explanation\n</details>\n",
+                marker, COLLAPSED_SUMMARY
+            )
+        );
+    }
+}
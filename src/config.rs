@@ -0,0 +1,420 @@
+extern crate toml;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use judgement::{is_rule_name, RuleSet};
+
+/// Path of the config file that repositories can use to override the server config. It is read
+/// from the base branch of the PR so a PR cannot loosen the rules it is judged by.
+pub const REPO_CONFIG_PATH: &str = ".github/prgnome.toml";
+
+const DEFAULT_STATUS_CONTEXT: &str = "mange/prgnome";
+
+#[derive(Debug, Fail)]
+pub enum ConfigError {
+    #[fail(display = "Could not read config file")]
+    ReadError(#[cause] io::Error),
+
+    #[fail(display = "{}", _0)]
+    ParseError(#[cause] toml::de::Error),
+
+    #[fail(display = "Override label \"{}\" waives unknown rule \"{}\"", _0, _1)]
+    UnknownOverrideRule(String, String),
+}
+
+/// Raw config, as read from one or more config files.
+///
+/// Config files are merged table by table, so a repository config only needs to mention the
+/// settings it wants to change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigSource {
+    value: toml::Value,
+}
+
+/// Fully resolved configuration for judging a PR.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub rules: RuleSet,
+    pub status: StatusConfig,
+    pub comments: CommentConfig,
+    pub shadow: Option<ShadowConfig>,
+    pub overrides: OverrideConfig,
+}
+
+/// How judgements are posted as commit statuses.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// Several deployments can judge the same repositories as long as they use different
+    /// contexts.
+    pub context: String,
+    /// Link to show on the status. `{owner}`, `{repo}`, `{number}`, and `{sha}` are replaced with
+    /// the details of the PR.
+    pub target_url: Option<String>,
+    /// URL where this server can be reached. When set, statuses link to the report on this
+    /// server unless `target_url` is set.
+    pub public_url: Option<String>,
+}
+
+/// Comment on PRs that are not approved, explaining every problem and how to fix it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CommentConfig {
+    pub enabled: bool,
+    pub on_success: OnSuccess,
+}
+
+/// What to do with the comment once the PR has no problems anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnSuccess {
+    Delete,
+    Collapse,
+}
+
+/// Labels that waive some of the rules, and who may apply them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OverrideConfig {
+    /// Override labels, and the names of the rules that they waive.
+    pub labels: BTreeMap<String, Vec<String>>,
+    /// When set, override labels only count if they were applied by someone with at least this
+    /// permission on the repository...
+    pub permission: Option<Permission>,
+    /// ...or by a member of one of these teams (like "acme/release-managers").
+    pub teams: Vec<String>,
+}
+
+/// Permission levels on a repository, from lowest to highest.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Read,
+    Triage,
+    Write,
+    Maintain,
+    Admin,
+}
+
+/// A candidate rule set that is evaluated next to the active one without affecting the posted
+/// status.
+#[derive(Debug, Clone)]
+pub struct ShadowConfig {
+    pub rules: RuleSet,
+    /// When set, the shadow judgement is posted as a separate status under this context.
+    pub status_context: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawConfig {
+    rules: toml::value::Table,
+    status: StatusConfig,
+    comments: CommentConfig,
+    shadow: Option<RawShadowConfig>,
+    overrides: OverrideConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawShadowConfig {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    status_context: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for StatusConfig {
+    fn default() -> StatusConfig {
+        StatusConfig {
+            context: DEFAULT_STATUS_CONTEXT.into(),
+            target_url: None,
+            public_url: None,
+        }
+    }
+}
+
+impl Default for OnSuccess {
+    fn default() -> OnSuccess {
+        OnSuccess::Delete
+    }
+}
+
+impl Default for OverrideConfig {
+    fn default() -> OverrideConfig {
+        let mut labels = BTreeMap::new();
+        labels.insert(
+            String::from("prgnome: allow-fixup"),
+            vec![
+                String::from("forbidden_intros"),
+                String::from("forbidden_messages"),
+            ],
+        );
+        labels.insert(
+            String::from("prgnome: allow-large"),
+            vec![String::from("max_commits"), String::from("change_size")],
+        );
+
+        OverrideConfig {
+            labels,
+            permission: None,
+            teams: Vec::new(),
+        }
+    }
+}
+
+impl OverrideConfig {
+    /// Whether the person who applied an override label has to be checked.
+    pub fn is_restricted(&self) -> bool {
+        self.permission.is_some() || !self.teams.is_empty()
+    }
+}
+
+impl Permission {
+    /// Parse a permission or role name from the Github API.
+    pub fn from_api(name: &str) -> Option<Permission> {
+        match name {
+            "read" | "pull" => Some(Permission::Read),
+            "triage" => Some(Permission::Triage),
+            "write" | "push" => Some(Permission::Write),
+            "maintain" => Some(Permission::Maintain),
+            "admin" => Some(Permission::Admin),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ConfigSource {
+    fn default() -> ConfigSource {
+        ConfigSource {
+            value: toml::Value::Table(Default::default()),
+        }
+    }
+}
+
+impl ConfigSource {
+    pub fn parse(input: &str) -> Result<ConfigSource, ConfigError> {
+        input
+            .parse()
+            .map(|value| ConfigSource { value })
+            .map_err(ConfigError::from)
+    }
+
+    pub fn read(path: &Path) -> Result<ConfigSource, ConfigError> {
+        let input = ::std::fs::read_to_string(path).map_err(ConfigError::ReadError)?;
+        ConfigSource::parse(&input)
+    }
+
+    /// Return a new source where the settings of `overrides` replace the ones in this one.
+    pub fn merged_with(&self, overrides: &ConfigSource) -> ConfigSource {
+        let mut value = self.value.clone();
+        merge_values(&mut value, &overrides.value);
+        ConfigSource { value }
+    }
+
+    pub fn config(&self) -> Result<Config, ConfigError> {
+        let raw: RawConfig = self.value.clone().try_into()?;
+
+        // Shadow rules start out as a copy of the active rules, so only the differences need to
+        // be configured.
+        let shadow = match (raw.shadow, self.value.get("shadow")) {
+            (Some(ref shadow), Some(shadow_value)) if shadow.enabled => {
+                let mut rules_value = toml::Value::Table(raw.rules.clone());
+                if let Some(shadow_rules) = shadow_value.get("rules") {
+                    merge_values(&mut rules_value, shadow_rules);
+                }
+                let rules_table = match rules_value {
+                    toml::Value::Table(table) => table,
+                    _ => Default::default(),
+                };
+
+                Some(ShadowConfig {
+                    rules: RuleSet::from_config(&rules_table)?,
+                    status_context: shadow.status_context.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        for (label, rules) in &raw.overrides.labels {
+            if let Some(unknown) = rules.iter().find(|rule| !is_rule_name(rule)) {
+                return Err(ConfigError::UnknownOverrideRule(
+                    label.clone(),
+                    unknown.clone(),
+                ));
+            }
+        }
+
+        Ok(Config {
+            rules: RuleSet::from_config(&raw.rules)?,
+            status: raw.status,
+            comments: raw.comments,
+            shadow,
+            overrides: raw.overrides,
+        })
+    }
+}
+
+fn merge_values(base: &mut toml::Value, overrides: &toml::Value) {
+    match (base, overrides) {
+        (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides.clone(),
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> ConfigError {
+        ConfigError::ParseError(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use judgement::{Intel, Judgement};
+
+    fn source(input: &str) -> ConfigSource {
+        ConfigSource::parse(input).unwrap()
+    }
+
+    fn judge(rules: &RuleSet, messages: &[&str]) -> Judgement {
+        let intel = Intel {
+            total_commits: messages.len() as u64,
+            commit_messages: messages.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+        intel.validate(rules)
+    }
+
+    #[test]
+    fn it_uses_default_rules_without_config() {
+        let config = ConfigSource::default().config().unwrap();
+        assert_eq!(config.rules.rule_names(), RuleSet::default().rule_names());
+        assert_eq!(config.status, StatusConfig::default());
+        assert!(config.shadow.is_none());
+    }
+
+    #[test]
+    fn it_reads_rules() {
+        let config = source("[rules.max_commits]\nmax = 1\n\n[rules.ignore_label]\nenabled = false")
+            .config()
+            .unwrap();
+        assert!(!config.rules.rule_names().contains(&"ignore_label"));
+        assert_ne!(judge(&config.rules, &["One", "Two"]), Judgement::Approved);
+        assert!(config.shadow.is_none());
+    }
+
+    #[test]
+    fn it_reads_status_settings() {
+        let config = source("[status]\ntarget_url = \"https://example.com/{sha}\"")
+            .config()
+            .unwrap();
+        assert_eq!(config.status.context, "mange/prgnome");
+        assert_eq!(
+            config.status.target_url,
+            Some(String::from("https://example.com/{sha}"))
+        );
+    }
+
+    #[test]
+    fn it_reads_comment_settings() {
+        let config = ConfigSource::default().config().unwrap();
+        assert!(!config.comments.enabled);
+        assert_eq!(config.comments.on_success, OnSuccess::Delete);
+
+        let config = source("[comments]\nenabled = true\non_success = \"collapse\"")
+            .config()
+            .unwrap();
+        assert!(config.comments.enabled);
+        assert_eq!(config.comments.on_success, OnSuccess::Collapse);
+    }
+
+    #[test]
+    fn it_reads_override_settings() {
+        let config = ConfigSource::default().config().unwrap();
+        assert_eq!(config.overrides, OverrideConfig::default());
+        assert!(!config.overrides.is_restricted());
+
+        let config = source(
+            "[overrides]\npermission = \"maintain\"\n\n[overrides.labels]\n\"yolo\" = [\"max_commits\"]",
+        ).config()
+        .unwrap();
+        assert_eq!(config.overrides.permission, Some(Permission::Maintain));
+        assert!(config.overrides.is_restricted());
+        assert_eq!(
+            config.overrides.labels.get("yolo"),
+            Some(&vec![String::from("max_commits")])
+        );
+        assert!(!config.overrides.labels.contains_key("prgnome: allow-fixup"));
+
+        assert!(
+            source("[overrides.labels]\n\"yolo\" = [\"no_such_rule\"]")
+                .config()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn it_orders_permissions() {
+        assert!(Permission::Admin > Permission::Maintain);
+        assert!(Permission::Write > Permission::Triage);
+        assert_eq!(Permission::from_api("push"), Some(Permission::Write));
+        assert_eq!(Permission::from_api("none"), None);
+    }
+
+    #[test]
+    fn it_rejects_invalid_config() {
+        assert!(source("[rules.max_commits]\nmax = \"many\"").config().is_err());
+        assert!(source("[rules.no_such_rule]").config().is_err());
+        assert!(ConfigSource::parse("[rules").is_err());
+    }
+
+    #[test]
+    fn it_merges_overrides_into_base_config() {
+        let server = source(
+            "[rules.max_commits]\nmax = 1\n\n[rules.forbidden_messages]\nmessages = [\"tmp\", \"foo\"]",
+        );
+        let repo = source("[rules.forbidden_messages]\nmessages = [\"bar\"]");
+
+        let config = server.merged_with(&repo).config().unwrap();
+        assert_eq!(judge(&config.rules, &["foo"]), Judgement::Approved);
+        assert_ne!(judge(&config.rules, &["bar"]), Judgement::Approved);
+        assert_ne!(judge(&config.rules, &["One", "Two"]), Judgement::Approved);
+    }
+
+    #[test]
+    fn it_bases_shadow_rules_on_active_rules() {
+        let config = source(
+            "[rules.max_commits]\nmax = 1\n\n[shadow]\nstatus_context = \"shadow\"\n\n[shadow.rules.forbidden_intros]\nenabled = false",
+        ).config()
+        .unwrap();
+
+        let shadow = config.shadow.unwrap();
+        assert!(!shadow.rules.rule_names().contains(&"forbidden_intros"));
+        assert_ne!(judge(&shadow.rules, &["One", "Two"]), Judgement::Approved);
+        assert_eq!(shadow.status_context, Some(String::from("shadow")));
+        assert!(config.rules.rule_names().contains(&"forbidden_intros"));
+    }
+
+    #[test]
+    fn it_allows_repos_to_disable_shadow_rules() {
+        let server = source("[shadow.rules.max_commits]\nmax = 5");
+        let repo = source("[shadow]\nenabled = false");
+
+        assert!(server.config().unwrap().shadow.is_some());
+        assert!(server.merged_with(&repo).config().unwrap().shadow.is_none());
+    }
+}
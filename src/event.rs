@@ -3,6 +3,11 @@ extern crate serde_json;
 #[derive(Debug)]
 pub enum Event {
     PullRequest(PullRequestEvent),
+    Push(PushEvent),
+    Installation(InstallationEvent),
+    InstallationRepositories(InstallationRepositoriesEvent),
+    Ping(PingEvent),
+    IssueComment(IssueCommentEvent),
     Unknown {
         name: String,
         payload: serde_json::Value,
@@ -17,6 +22,8 @@ pub enum PullRequestEvent {
         pull_request: PullRequest,
         repository: Repository,
         installation: Installation,
+        /// The user that applied the label.
+        sender: User,
     },
     Unlabeled {
         label: Label,
@@ -24,16 +31,153 @@ pub enum PullRequestEvent {
         repository: Repository,
         installation: Installation,
     },
+    Opened {
+        pull_request: PullRequest,
+        repository: Repository,
+        installation: Installation,
+    },
+    Reopened {
+        pull_request: PullRequest,
+        repository: Repository,
+        installation: Installation,
+    },
     Synchronize {
         pull_request: PullRequest,
         repository: Repository,
         installation: Installation,
     },
+    Edited {
+        pull_request: PullRequest,
+        repository: Repository,
+        installation: Installation,
+    },
 
     #[serde(other)]
     Other, // { payload: serde_json::Value, },
 }
 
+/// A comment on an issue or a PR. Only new comments are parsed.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum IssueCommentEvent {
+    Created {
+        issue: Issue,
+        comment: Comment,
+        repository: Repository,
+        installation: Installation,
+    },
+
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    /// Only set if the issue is a PR.
+    pub pull_request: Option<IssuePullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssuePullRequest {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Comment {
+    pub id: u64,
+    pub body: String,
+    pub user: User,
+}
+
+/// Sent when a webhook is set up, like when the app is created.
+#[derive(Debug, Deserialize)]
+pub struct PingEvent {
+    pub zen: String,
+    pub hook_id: u64,
+    pub hook: Hook,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Hook {
+    /// Events the webhook is subscribed to. "*" means every event.
+    pub events: Vec<String>,
+}
+
+/// The app was installed on, or uninstalled from, an account.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InstallationEvent {
+    Created {
+        installation: Installation,
+        /// Repositories the app was installed on.
+        #[serde(default)]
+        repositories: Vec<InstalledRepository>,
+    },
+    Deleted {
+        installation: Installation,
+        /// Repositories the app was uninstalled from.
+        #[serde(default)]
+        repositories: Vec<InstalledRepository>,
+    },
+
+    #[serde(other)]
+    Other,
+}
+
+/// Repositories were added to, or removed from, an installation of the app.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InstallationRepositoriesEvent {
+    Added {
+        installation: Installation,
+        repositories_added: Vec<InstalledRepository>,
+    },
+    Removed {
+        installation: Installation,
+        repositories_removed: Vec<InstalledRepository>,
+    },
+
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstalledRepository {
+    pub full_name: String,
+}
+
+/// Commits pushed to a branch or tag. Only what is needed to notice config changes is parsed.
+#[derive(Debug, Deserialize)]
+pub struct PushEvent {
+    #[serde(rename = "ref")]
+    pub ref_name: String,
+    /// The pushed commits. Github includes at most 20 of them.
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+    /// The last pushed commit, which is included even if the list of commits is cut short.
+    pub head_commit: Option<PushCommit>,
+    pub repository: PushRepository,
+    pub installation: Option<Installation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushCommit {
+    #[serde(default)]
+    pub added: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub modified: Vec<String>,
+}
+
+/// The repository of a push event. It differs from the other events, where `url` is the API URL.
+#[derive(Debug, Deserialize)]
+pub struct PushRepository {
+    pub full_name: String,
+    pub default_branch: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Label {
     pub id: u64,
@@ -53,6 +197,7 @@ pub struct PullRequest {
     pub id: u64,
     pub number: u64,
     pub title: String,
+    pub body: Option<String>,
     pub labels: Vec<Label>,
     pub head: Commit,
     pub base: Commit,
@@ -66,6 +211,11 @@ pub struct Repository {
     pub url: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub login: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Installation {
     pub id: u64,
@@ -81,6 +231,21 @@ impl Event {
     pub fn parse_json(event_name: &str, json: &str) -> Result<Event, EventError> {
         match event_name {
             "pull_request" => PullRequestEvent::parse_json(json).map(Event::PullRequest),
+            "push" => serde_json::from_str(json)
+                .map(Event::Push)
+                .map_err(EventError::from),
+            "issue_comment" => serde_json::from_str(json)
+                .map(Event::IssueComment)
+                .map_err(EventError::from),
+            "ping" => serde_json::from_str(json)
+                .map(Event::Ping)
+                .map_err(EventError::from),
+            "installation" => serde_json::from_str(json)
+                .map(Event::Installation)
+                .map_err(EventError::from),
+            "installation_repositories" => serde_json::from_str(json)
+                .map(Event::InstallationRepositories)
+                .map_err(EventError::from),
             _ => serde_json::from_str(json)
                 .map(|value| Event::Unknown {
                     name: event_name.to_owned(),
@@ -99,7 +264,22 @@ impl PullRequestEvent {
         match self {
             PullRequestEvent::Labeled { repository, .. } => Some(&repository.url),
             PullRequestEvent::Unlabeled { repository, .. } => Some(&repository.url),
+            PullRequestEvent::Opened { repository, .. } => Some(&repository.url),
+            PullRequestEvent::Reopened { repository, .. } => Some(&repository.url),
             PullRequestEvent::Synchronize { repository, .. } => Some(&repository.url),
+            PullRequestEvent::Edited { repository, .. } => Some(&repository.url),
+            PullRequestEvent::Other => None,
+        }
+    }
+
+    pub fn repository(&self) -> Option<&Repository> {
+        match self {
+            PullRequestEvent::Labeled { repository, .. } => Some(repository),
+            PullRequestEvent::Unlabeled { repository, .. } => Some(repository),
+            PullRequestEvent::Opened { repository, .. } => Some(repository),
+            PullRequestEvent::Reopened { repository, .. } => Some(repository),
+            PullRequestEvent::Synchronize { repository, .. } => Some(repository),
+            PullRequestEvent::Edited { repository, .. } => Some(repository),
             PullRequestEvent::Other => None,
         }
     }
@@ -108,7 +288,10 @@ impl PullRequestEvent {
         match self {
             PullRequestEvent::Labeled { pull_request, .. } => Some(pull_request),
             PullRequestEvent::Unlabeled { pull_request, .. } => Some(pull_request),
+            PullRequestEvent::Opened { pull_request, .. } => Some(pull_request),
+            PullRequestEvent::Reopened { pull_request, .. } => Some(pull_request),
             PullRequestEvent::Synchronize { pull_request, .. } => Some(pull_request),
+            PullRequestEvent::Edited { pull_request, .. } => Some(pull_request),
             PullRequestEvent::Other => None,
         }
     }
@@ -117,10 +300,67 @@ impl PullRequestEvent {
         match self {
             PullRequestEvent::Labeled { installation, .. } => Some(installation),
             PullRequestEvent::Unlabeled { installation, .. } => Some(installation),
+            PullRequestEvent::Opened { installation, .. } => Some(installation),
+            PullRequestEvent::Reopened { installation, .. } => Some(installation),
             PullRequestEvent::Synchronize { installation, .. } => Some(installation),
+            PullRequestEvent::Edited { installation, .. } => Some(installation),
             PullRequestEvent::Other => None,
         }
     }
+
+    /// The label that was applied and the user that applied it, for labeled events.
+    pub fn applied_label(&self) -> Option<(&Label, &User)> {
+        match self {
+            PullRequestEvent::Labeled { label, sender, .. } => Some((label, sender)),
+            _ => None,
+        }
+    }
+}
+
+impl PingEvent {
+    pub fn is_subscribed_to(&self, event_name: &str) -> bool {
+        self.hook
+            .events
+            .iter()
+            .any(|event| event == event_name || event == "*")
+    }
+}
+
+/// The most commits Github includes in a push event. Larger pushes are cut short.
+const MAX_PUSH_COMMITS: usize = 20;
+
+impl PushEvent {
+    /// Name of the pushed branch, unless a tag was pushed.
+    pub fn branch(&self) -> Option<&str> {
+        const PREFIX: &str = "refs/heads/";
+        if self.ref_name.starts_with(PREFIX) {
+            Some(&self.ref_name[PREFIX.len()..])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_to_default_branch(&self) -> bool {
+        self.branch() == Some(self.repository.default_branch.as_str())
+    }
+
+    /// Whether any of the pushed commits added, changed, or removed the file. Pushes with more
+    /// commits than the event includes might touch it in the ones that are left out, so they are
+    /// assumed to.
+    pub fn touches(&self, path: &str) -> bool {
+        if self.commits.len() >= MAX_PUSH_COMMITS {
+            return true;
+        }
+
+        self.commits.iter().chain(&self.head_commit).any(|commit| {
+            commit
+                .added
+                .iter()
+                .chain(&commit.removed)
+                .chain(&commit.modified)
+                .any(|changed| changed == path)
+        })
+    }
 }
 
 impl From<serde_json::Error> for EventError {
@@ -129,23 +369,36 @@ impl From<serde_json::Error> for EventError {
     }
 }
 
+/// Read a recorded payload from `tests/fixtures`.
 #[cfg(test)]
-mod tests {
-    use super::*;
+pub fn read_fixture(name: &str) -> String {
+    let path = format!("tests/fixtures/{}", name);
+    ::std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!("Failed to read {}: {}", path, err);
+    })
+}
 
-    fn read_fixture(name: &str) -> String {
-        let path = format!("tests/fixtures/{}", name);
-        ::std::fs::read_to_string(&path).unwrap_or_else(|err| {
-            panic!("Failed to read {}: {}", path, err);
-        })
+/// Parse a recorded pull_request webhook from `tests/fixtures`.
+#[cfg(test)]
+pub fn pr_event(name: &str) -> PullRequestEvent {
+    match Event::parse_json("pull_request", &read_fixture(name)) {
+        Ok(Event::PullRequest(pr_event)) => pr_event,
+        other => panic!("Expected a pull_request event, got {:#?}", other),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn it_parses_labeled_pr_webhooks() {
         let data = read_fixture("webhook_pr_labeled.json");
         let event: Event = Event::parse_json("pull_request", &data).unwrap();
         match event {
-            Event::PullRequest(PullRequestEvent::Labeled { .. }) => {}
+            Event::PullRequest(PullRequestEvent::Labeled { sender, .. }) => {
+                assert_eq!(sender.login, "Mange");
+            }
             other => panic!(
                 "Parsed as a {:#?}, but expected an Event::PullRequest(PullRequest::Labeled)",
                 other
@@ -153,6 +406,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_parses_edited_pr_webhooks() {
+        let data = read_fixture("webhook_pr_edited.json");
+        let event: Event = Event::parse_json("pull_request", &data).unwrap();
+        match event {
+            Event::PullRequest(PullRequestEvent::Edited { pull_request, .. }) => {
+                assert_eq!(pull_request.title, "PROJ-42: Example PR");
+                assert_eq!(
+                    pull_request.body,
+                    Some(String::from("This PR is stills a Work In Progress!"))
+                );
+            }
+            other => panic!(
+                "Parsed as a {:#?}, but expected an Event::PullRequest(PullRequest::Edited)",
+                other
+            ),
+        }
+    }
+
     #[test]
     fn it_parses_unlabeled_pr_webhooks() {
         let data = read_fixture("webhook_pr_unlabeled.json");
@@ -166,6 +438,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_parses_opened_pr_webhooks() {
+        let data = read_fixture("webhook_pr_opened.json");
+        let event: Event = Event::parse_json("pull_request", &data).unwrap();
+        match event {
+            Event::PullRequest(PullRequestEvent::Opened { pull_request, .. }) => {
+                assert_eq!(pull_request.number, 1);
+            }
+            other => panic!(
+                "Parsed as a {:#?}, but expected an Event::PullRequest(PullRequest::Opened)",
+                other
+            ),
+        }
+    }
+
     #[test]
     fn it_parses_synchronized_pr_webhooks() {
         let data = read_fixture("webhook_pr_synchronized.json");
@@ -194,6 +481,123 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_parses_push_webhooks() {
+        let data = read_fixture("webhook_push.json");
+        let event: Event = Event::parse_json("push", &data).unwrap();
+        match event {
+            Event::Push(push_event) => {
+                assert_eq!(push_event.branch(), Some("master"));
+                assert!(push_event.is_to_default_branch());
+                assert!(push_event.touches(".github/prgnome.toml"));
+                assert!(push_event.touches("README.md"));
+                assert!(!push_event.touches("src/main.rs"));
+                assert_eq!(push_event.installation.map(|i| i.id), Some(441056));
+            }
+            other => panic!("Parsed as a {:#?}, but expected an Event::Push", other),
+        }
+    }
+
+    fn push_with_commits(commits: usize, head_commit: &str) -> PushEvent {
+        let mut data: serde_json::Value =
+            serde_json::from_str(&read_fixture("webhook_push.json")).unwrap();
+        let head_commit: serde_json::Value = serde_json::from_str(head_commit).unwrap();
+        data["commits"] = serde_json::Value::Array(vec![head_commit.clone(); commits]);
+        data["head_commit"] = head_commit;
+        match Event::parse_json("push", &data.to_string()) {
+            Ok(Event::Push(push_event)) => push_event,
+            other => panic!("Parsed as a {:#?}, but expected an Event::Push", other),
+        }
+    }
+
+    #[test]
+    fn it_checks_the_head_commit_of_pushes() {
+        let push_event = push_with_commits(0, r#"{"modified": ["README.md"]}"#);
+
+        assert!(push_event.touches("README.md"));
+        assert!(!push_event.touches(".github/prgnome.toml"));
+    }
+
+    #[test]
+    fn it_assumes_that_cut_short_pushes_touch_every_file() {
+        let head_commit = r#"{"modified": ["README.md"]}"#;
+
+        assert!(!push_with_commits(19, head_commit).touches(".github/prgnome.toml"));
+        assert!(push_with_commits(20, head_commit).touches(".github/prgnome.toml"));
+    }
+
+    #[test]
+    fn it_parses_issue_comment_webhooks() {
+        let data = read_fixture("webhook_issue_comment_created.json");
+        let event: Event = Event::parse_json("issue_comment", &data).unwrap();
+        match event {
+            Event::IssueComment(IssueCommentEvent::Created { issue, comment, .. }) => {
+                assert_eq!(issue.number, 1);
+                assert!(issue.pull_request.is_some());
+                assert_eq!(comment.body, "/prgnome recheck");
+                assert_eq!(comment.user.login, "Mange");
+            }
+            other => panic!(
+                "Parsed as a {:#?}, but expected an Event::IssueComment(Created)",
+                other
+            ),
+        }
+    }
+
+    #[test]
+    fn it_parses_ping_webhooks() {
+        let data = read_fixture("webhook_ping.json");
+        let event: Event = Event::parse_json("ping", &data).unwrap();
+        match event {
+            Event::Ping(ping_event) => {
+                assert_eq!(ping_event.zen, "Design for failure.");
+                assert_eq!(ping_event.hook_id, 74624183);
+                assert!(ping_event.is_subscribed_to("pull_request"));
+                assert!(!ping_event.is_subscribed_to("push"));
+            }
+            other => panic!("Parsed as a {:#?}, but expected an Event::Ping", other),
+        }
+    }
+
+    #[test]
+    fn it_parses_installation_webhooks() {
+        let data = read_fixture("webhook_installation_created.json");
+        let event: Event = Event::parse_json("installation", &data).unwrap();
+        match event {
+            Event::Installation(InstallationEvent::Created {
+                installation,
+                repositories,
+            }) => {
+                assert_eq!(installation.id, 441056);
+                let names: Vec<&str> = repositories.iter().map(|r| r.full_name.as_str()).collect();
+                assert_eq!(names, vec!["example/example-repo", "example/other-repo"]);
+            }
+            other => panic!(
+                "Parsed as a {:#?}, but expected an Event::Installation(Created)",
+                other
+            ),
+        }
+    }
+
+    #[test]
+    fn it_parses_installation_repositories_webhooks() {
+        let data = read_fixture("webhook_installation_repositories_removed.json");
+        let event: Event = Event::parse_json("installation_repositories", &data).unwrap();
+        match event {
+            Event::InstallationRepositories(InstallationRepositoriesEvent::Removed {
+                installation,
+                repositories_removed,
+            }) => {
+                assert_eq!(installation.id, 441056);
+                assert_eq!(repositories_removed[0].full_name, "example/other-repo");
+            }
+            other => panic!(
+                "Parsed as a {:#?}, but expected an Event::InstallationRepositories(Removed)",
+                other
+            ),
+        }
+    }
+
     #[test]
     fn it_stores_payload_on_unknown_events() {
         let data = r#"{"hello":"world"}"#;
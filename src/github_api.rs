@@ -2,12 +2,20 @@ extern crate jsonwebtoken as jwt;
 extern crate reqwest;
 
 use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+
+use event::{Installation, PullRequest, Repository};
+
+const API_URL: &str = "https://api.github.com";
 
 /// Expiry time for a JWT token in seconds. 10 minutes is the maximum allowed.
 ///
 /// JWT tokens are used to create normal auth tokens (that expire every hour).
 const EXPIRY_SECONDS: u64 = 60; // 1 minute
 
+/// Largest page size that Github allows for lists.
+const PER_PAGE: usize = 100;
+
 #[derive(Debug, Fail)]
 pub enum ApiError {
     #[fail(display = "Failed to generate JWT")]
@@ -38,6 +46,11 @@ fn unix_timestamp() -> u64 {
         .as_secs()
 }
 
+/// API URL of a repository, from its full name (like "owner/repo").
+pub fn repo_url(full_name: &str) -> String {
+    format!("{api}/repos/{repo}", api = API_URL, repo = full_name)
+}
+
 impl Client {
     pub fn new(app_id: u64, private_key: Vec<u8>) -> Client {
         Client {
@@ -66,6 +79,73 @@ impl Client {
         Ok(body.token)
     }
 
+    /// Find the installation of the app on a repository.
+    pub fn get_repository_installation(&self, full_name: &str) -> Result<Installation, ApiError> {
+        let url = format!("{repo}/installation", repo = repo_url(full_name));
+        let jwt = self.new_jwt()?;
+        debug!("GET {}", url);
+        let mut response = reqwest::Client::new()
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", jwt))
+            .header("Accept", "application/vnd.github.machine-man-preview+json")
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
+    /// How many more requests can be made with the auth token, and when that resets.
+    pub fn get_rate_limit(&self, auth_token: &str) -> Result<RateLimit, ApiError> {
+        let full_path = format!("{api}/rate_limit", api = API_URL);
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", full_path);
+        let mut response = add_headers(client.get(&full_path), auth_token)
+            .send()?
+            .error_for_status()?;
+
+        let status: RateLimitStatus = response.json()?;
+        Ok(status.resources.core)
+    }
+
+    pub fn get_repository(&self, auth_token: &str, repo_url: &str) -> Result<Repository, ApiError> {
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", repo_url);
+        let mut response = add_headers(client.get(repo_url), auth_token)
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
+    pub fn list_open_pull_requests(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+    ) -> Result<Vec<PullRequest>, ApiError> {
+        // Only open PRs are listed by default.
+        let full_path = format!("{repo}/pulls", repo = repo_url);
+        get_all_pages(&full_path, auth_token)
+    }
+
+    pub fn get_pull_request(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        number: u64,
+    ) -> Result<PullRequest, ApiError> {
+        let full_path = format!("{repo}/pulls/{number}", repo = repo_url, number = number);
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", full_path);
+        let mut response = add_headers(client.get(&full_path), auth_token)
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
     pub fn create_status(
         &self,
         auth_token: &str,
@@ -85,6 +165,25 @@ impl Client {
         response.json().map_err(ApiError::from)
     }
 
+    /// Get the latest status of every context on a commit.
+    pub fn get_combined_status(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        sha: &str,
+    ) -> Result<CombinedStatus, ApiError> {
+        let full_path = format!("{repo}/commits/{sha}/status", repo = repo_url, sha = sha);
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", full_path);
+        let mut response = add_headers(client.get(&full_path), auth_token)
+            .query(&[("per_page", PER_PAGE)])
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
     pub fn list_commits_in_range(
         &self,
         auth_token: &str,
@@ -108,6 +207,222 @@ impl Client {
         response.json().map_err(ApiError::from)
     }
 
+    /// List the files changed by a PR. Github lists at most 3000 files.
+    pub fn list_pull_request_files(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        number: u64,
+    ) -> Result<Vec<PullRequestFile>, ApiError> {
+        let full_path = format!(
+            "{repo}/pulls/{number}/files",
+            repo = repo_url,
+            number = number
+        );
+        get_all_pages(&full_path, auth_token)
+    }
+
+    /// Read a file in the repo at the given ref. Returns `None` if the file does not exist.
+    pub fn get_file_contents(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        path: &str,
+        ref_name: &str,
+    ) -> Result<Option<String>, ApiError> {
+        let full_path = format!("{repo}/contents/{path}", repo = repo_url, path = path);
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", full_path);
+        let response = client
+            .get(&full_path)
+            .query(&[("ref", ref_name)])
+            .header("Accept", "application/vnd.github.v3.raw")
+            .header("Authorization", format!("token {}", auth_token))
+            .send()?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        response
+            .error_for_status()?
+            .text()
+            .map(Some)
+            .map_err(ApiError::from)
+    }
+
+    pub fn list_issue_comments(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        number: u64,
+    ) -> Result<Vec<IssueComment>, ApiError> {
+        let full_path = format!(
+            "{repo}/issues/{number}/comments",
+            repo = repo_url,
+            number = number
+        );
+        get_all_pages(&full_path, auth_token)
+    }
+
+    pub fn create_issue_comment(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        number: u64,
+        body: &str,
+    ) -> Result<IssueComment, ApiError> {
+        let full_path = format!(
+            "{repo}/issues/{number}/comments",
+            repo = repo_url,
+            number = number
+        );
+        let client = reqwest::Client::new();
+
+        debug!("POST {}", full_path);
+        let mut response = add_headers(client.post(&full_path), auth_token)
+            .json(&CommentInput { body })
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
+    pub fn update_issue_comment(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        comment_id: u64,
+        body: &str,
+    ) -> Result<IssueComment, ApiError> {
+        let full_path = format!(
+            "{repo}/issues/comments/{id}",
+            repo = repo_url,
+            id = comment_id
+        );
+        let client = reqwest::Client::new();
+
+        debug!("PATCH {}", full_path);
+        let mut response = add_headers(client.patch(&full_path), auth_token)
+            .json(&CommentInput { body })
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
+    pub fn delete_issue_comment(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        comment_id: u64,
+    ) -> Result<(), ApiError> {
+        let full_path = format!(
+            "{repo}/issues/comments/{id}",
+            repo = repo_url,
+            id = comment_id
+        );
+        let client = reqwest::Client::new();
+
+        debug!("DELETE {}", full_path);
+        add_headers(client.delete(&full_path), auth_token)
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    pub fn list_issue_events(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        number: u64,
+    ) -> Result<Vec<IssueEvent>, ApiError> {
+        let full_path = format!(
+            "{repo}/issues/{number}/events",
+            repo = repo_url,
+            number = number
+        );
+        get_all_pages(&full_path, auth_token)
+    }
+
+    /// React to a comment, like with "+1" or "eyes".
+    pub fn create_comment_reaction(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        comment_id: u64,
+        content: &str,
+    ) -> Result<(), ApiError> {
+        let full_path = format!(
+            "{repo}/issues/comments/{id}/reactions",
+            repo = repo_url,
+            id = comment_id
+        );
+        let client = reqwest::Client::new();
+
+        debug!("POST {}", full_path);
+        client
+            .post(&full_path)
+            .header("Accept", "application/vnd.github.squirrel-girl-preview+json")
+            .header("Authorization", format!("token {}", auth_token))
+            .json(&ReactionInput { content })
+            .send()?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Get the permission that a user has on a repository.
+    pub fn get_collaborator_permission(
+        &self,
+        auth_token: &str,
+        repo_url: &str,
+        login: &str,
+    ) -> Result<CollaboratorPermission, ApiError> {
+        let full_path = format!(
+            "{repo}/collaborators/{login}/permission",
+            repo = repo_url,
+            login = login
+        );
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", full_path);
+        let mut response = add_headers(client.get(&full_path), auth_token)
+            .send()?
+            .error_for_status()?;
+
+        response.json().map_err(ApiError::from)
+    }
+
+    /// Check if a user is an active member of a team in an organization.
+    pub fn is_team_member(
+        &self,
+        auth_token: &str,
+        org: &str,
+        team_slug: &str,
+        login: &str,
+    ) -> Result<bool, ApiError> {
+        let full_path = format!(
+            "{api}/orgs/{org}/teams/{team}/memberships/{login}",
+            api = API_URL,
+            org = org,
+            team = team_slug,
+            login = login
+        );
+        let client = reqwest::Client::new();
+
+        debug!("GET {}", full_path);
+        let response = add_headers(client.get(&full_path), auth_token).send()?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        let membership: TeamMembership = response.error_for_status()?.json()?;
+        Ok(membership.state == "active")
+    }
+
     fn new_jwt(&self) -> Result<String, ApiError> {
         use self::jwt::{Algorithm, Header};
         let now = unix_timestamp();
@@ -130,6 +445,33 @@ fn add_headers(req: RequestBuilder, auth_token: &str) -> RequestBuilder {
         .header("Authorization", format!("token {}", auth_token))
 }
 
+/// Load every page of a list, stopping at the first page that is not full.
+fn get_all_pages<T: DeserializeOwned>(
+    full_path: &str,
+    auth_token: &str,
+) -> Result<Vec<T>, ApiError> {
+    let client = reqwest::Client::new();
+    let mut items = Vec::new();
+
+    for page in 1.. {
+        debug!("GET {} (page {})", full_path, page);
+        let mut response = add_headers(client.get(full_path), auth_token)
+            .query(&[("per_page", PER_PAGE), ("page", page)])
+            .send()?
+            .error_for_status()?;
+
+        let page_items: Vec<T> = response.json()?;
+        let is_last_page = page_items.len() < PER_PAGE;
+        items.extend(page_items);
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(items)
+}
+
 impl From<reqwest::Error> for ApiError {
     fn from(error: reqwest::Error) -> ApiError {
         ApiError::NetworkError(error)
@@ -163,21 +505,129 @@ pub enum State {
     Other,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct CommitList {
     pub total_commits: u64,
     pub commits: Vec<CommitInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct CommitInfo {
     pub sha: String,
     pub commit: Commit,
+    #[serde(default)]
+    pub parents: Vec<CommitRef>,
+    /// Github accounts of the author and committer, if Github could match their emails.
+    pub author: Option<User>,
+    pub committer: Option<User>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Commit {
     pub message: String,
+    pub author: Option<GitActor>,
+    pub committer: Option<GitActor>,
+    pub verification: Option<Verification>,
+}
+
+/// Github's verdict on the signature of a commit.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Verification {
+    pub verified: bool,
+    /// Why the commit is or isn't verified, like "valid", "unsigned", or "unknown_key".
+    pub reason: String,
+}
+
+/// Author or committer of a commit, as recorded in git.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GitActor {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitRef {
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PullRequestFile {
+    pub filename: String,
+    /// "added", "removed", "modified", "renamed", etc.
+    pub status: String,
+    pub additions: u64,
+    pub deletions: u64,
+    /// Old name of renamed files.
+    pub previous_filename: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueComment {
+    pub id: u64,
+    pub body: String,
+    pub user: User,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub login: String,
+    #[serde(rename = "type")]
+    pub user_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueEvent {
+    /// Kind of event, like "labeled" or "closed".
+    pub event: String,
+    pub actor: Option<User>,
+    /// Only set for "labeled" and "unlabeled" events.
+    pub label: Option<IssueEventLabel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueEventLabel {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollaboratorPermission {
+    /// One of "admin", "write", "read", or "none".
+    pub permission: String,
+    /// Finer grained role, like "maintain" or "triage". Not set by older Github versions.
+    pub role_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamMembership {
+    state: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitStatus {
+    resources: RateLimitResources,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResources {
+    core: RateLimit,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// When the limit resets, in seconds since the Unix epoch.
+    pub reset: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct CommentInput<'a> {
+    body: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ReactionInput<'a> {
+    content: &'a str,
 }
 
 #[derive(Debug, Deserialize)]
@@ -193,3 +643,10 @@ pub struct Status {
     pub description: Option<String>,
     pub context: Option<String>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct CombinedStatus {
+    pub state: State,
+    /// The latest status of each context.
+    pub statuses: Vec<Status>,
+}
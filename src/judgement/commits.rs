@@ -0,0 +1,298 @@
+use super::{rebase_hint, Intel, Rule, Violation};
+
+const FORBIDDEN_INTROS: [&str; 3] = ["wip", "fixup!", "squash!"];
+const FORBIDDEN_MESSAGES: [&str; 1] = ["tmp"];
+
+const MAX_COMMITS: u64 = 50;
+
+/// How to get rid of temporary commits.
+const SQUASH_OR_REWORD: &str = "squash them into the commits they belong to, or to reword them";
+
+/// Fail PRs with too many commits.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MaxCommits {
+    pub max: u64,
+}
+
+/// Fail PRs with temporary commits, recognized by their whole (short) message.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ForbiddenMessages {
+    pub messages: Vec<String>,
+}
+
+/// Fail PRs with temporary commits, recognized by the start of their message.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ForbiddenIntros {
+    pub intros: Vec<String>,
+}
+
+impl Default for MaxCommits {
+    fn default() -> MaxCommits {
+        MaxCommits { max: MAX_COMMITS }
+    }
+}
+
+impl Default for ForbiddenMessages {
+    fn default() -> ForbiddenMessages {
+        ForbiddenMessages {
+            messages: FORBIDDEN_MESSAGES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for ForbiddenIntros {
+    fn default() -> ForbiddenIntros {
+        ForbiddenIntros {
+            intros: FORBIDDEN_INTROS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Rule for MaxCommits {
+    fn name(&self) -> &'static str {
+        "max_commits"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        if intel.total_commits > self.max {
+            vec![Violation::new(
+                self,
+                format!("Rebase until you have {} commits or fewer", self.max),
+                rebase_hint(
+                    &format!(
+                        "This PR has {} commits, but at most {} are allowed.",
+                        intel.total_commits, self.max
+                    ),
+                    "squash related commits together",
+                ),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl Rule for ForbiddenMessages {
+    fn name(&self) -> &'static str {
+        "forbidden_messages"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            .filter(|message| {
+                self.messages
+                    .iter()
+                    .any(|forbidden| forbidden.eq_ignore_ascii_case(message))
+            }).map(|message| {
+                Violation::new(
+                    self,
+                    format!("Rebase away \"{}\"", message),
+                    rebase_hint("Temporary commits should not be merged.", SQUASH_OR_REWORD),
+                )
+            }).collect()
+    }
+}
+
+impl Rule for ForbiddenIntros {
+    fn name(&self) -> &'static str {
+        "forbidden_intros"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for message in &intel.commit_messages {
+            for forbidden_intro in &self.intros {
+                if starts_with_ignore_ascii_case(message, forbidden_intro) {
+                    violations.push(Violation::new(
+                        self,
+                        format!("Rebase away \"{}\"", message),
+                        intro_hint(forbidden_intro),
+                    ));
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+fn starts_with_ignore_ascii_case(message: &str, intro: &str) -> bool {
+    // Messages can start with any character, so don't slice in the middle of one.
+    message
+        .get(..intro.len())
+        .map_or(false, |start| start.eq_ignore_ascii_case(intro))
+}
+
+fn intro_hint(intro: &str) -> String {
+    match intro.to_ascii_lowercase().as_str() {
+        "fixup!" | "squash!" => format!(
+            "Commits starting with \"{}\" are meant to be squashed into the commit they amend \
+             before merging. Run git rebase -i --autosquash onto the base branch to do it \
+             automatically.",
+            intro
+        ),
+        _ => rebase_hint(
+            &format!("Commits starting with \"{}\" are not meant to be merged.", intro),
+            SQUASH_OR_REWORD,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(violations: Vec<Violation>) -> Vec<String> {
+        violations.into_iter().map(|v| v.message).collect()
+    }
+
+    #[test]
+    fn it_has_a_valid_whitelist() {
+        for intro in FORBIDDEN_INTROS.iter() {
+            assert_eq!(&intro.to_ascii_lowercase(), intro);
+        }
+    }
+
+    #[test]
+    fn it_forbids_intel_with_forbidden_commit_intros() {
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("Initial commit"),
+                String::from("fixup! Initial commit"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(ForbiddenIntros::default().check(&intel)),
+            vec!["Rebase away \"fixup! Initial commit\""]
+        );
+    }
+
+    #[test]
+    fn it_forbids_intel_with_forbidden_commit_messages() {
+        let intel = Intel {
+            commit_messages: vec![String::from("Initial commit"), String::from("tmp")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(ForbiddenMessages::default().check(&intel)),
+            vec!["Rebase away \"tmp\""]
+        );
+    }
+
+    #[test]
+    fn it_is_okay_with_tricky_but_ok_messages() {
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("Clear out tmp"),
+                String::from("Activate WIP gateway"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(ForbiddenMessages::default().check(&intel), vec![]);
+        assert_eq!(ForbiddenIntros::default().check(&intel), vec![]);
+    }
+
+    #[test]
+    fn it_handles_non_ascii_and_long_messages() {
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("Fix sorting of the names of Åsa and Örjan"),
+                String::from("fixup! Fix sorting of the names of Åsa and Örjan"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(ForbiddenIntros::default().check(&intel)),
+            vec!["Rebase away \"fixup! Fix sorting of the names of Åsa and Örjan\""]
+        );
+        assert_eq!(ForbiddenMessages::default().check(&intel), vec![]);
+
+        let intros = ForbiddenIntros {
+            intros: vec![String::from("FIX SORTING OF THE NAMES OF Åsa")],
+        };
+        assert_eq!(
+            messages(intros.check(&intel)),
+            vec!["Rebase away \"Fix sorting of the names of Åsa and Örjan\""]
+        );
+
+        let forbidden_messages = ForbiddenMessages {
+            messages: vec![String::from("fix sorting of the names of Åsa and Örjan")],
+        };
+        assert_eq!(
+            messages(forbidden_messages.check(&intel)),
+            vec!["Rebase away \"Fix sorting of the names of Åsa and Örjan\""]
+        );
+    }
+
+    #[test]
+    fn it_forbids_too_many_commits() {
+        let intel = Intel {
+            total_commits: MAX_COMMITS + 1,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(MaxCommits::default().check(&intel)),
+            vec![format!("Rebase until you have {} commits or fewer", MAX_COMMITS)]
+        );
+
+        let intel = Intel {
+            total_commits: MAX_COMMITS,
+            ..Default::default()
+        };
+        assert_eq!(MaxCommits::default().check(&intel), vec![]);
+    }
+
+    #[test]
+    fn it_uses_configured_intros_and_messages() {
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("drop me: debug logging"),
+                String::from("fixup! Initial commit"),
+                String::from("Foo"),
+            ],
+            ..Default::default()
+        };
+
+        let intros = ForbiddenIntros {
+            intros: vec![String::from("DROP ME")],
+        };
+        assert_eq!(
+            messages(intros.check(&intel)),
+            vec!["Rebase away \"drop me: debug logging\""]
+        );
+
+        let forbidden_messages = ForbiddenMessages {
+            messages: vec![String::from("foo")],
+        };
+        assert_eq!(
+            messages(forbidden_messages.check(&intel)),
+            vec!["Rebase away \"Foo\""]
+        );
+    }
+
+    #[test]
+    fn it_explains_how_to_fix_violations() {
+        let intel = Intel {
+            commit_messages: vec![String::from("fixup! Initial commit"), String::from("WIP")],
+            ..Default::default()
+        };
+
+        let violations = ForbiddenIntros::default().check(&intel);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].rule, "forbidden_intros");
+        assert!(violations[0].hint.contains("git rebase -i --autosquash"));
+        assert!(violations[1].hint.contains("\"wip\" are not meant to be merged"));
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt;
+
+use super::{reword_hint, Intel, Rule, Violation};
+
+const DEFAULT_TYPES: [&str; 11] = [
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+];
+
+/// Require every commit message to be a Conventional Commit
+/// (https://www.conventionalcommits.org/).
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ConventionalCommits {
+    pub types: Vec<String>,
+    /// Allowed scopes. Any scope is allowed when this is not set.
+    pub scopes: Option<Vec<String>>,
+}
+
+/// The parts of a Conventional Commit message that are validated.
+#[derive(Debug, PartialEq)]
+pub struct ConventionalCommit<'a> {
+    pub commit_type: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    MissingType,
+    EmptyScope,
+    UnclosedScope,
+    MissingSeparator,
+    MissingDescription,
+    MissingBlankLine,
+    EmptyBreakingChange,
+    LowercaseBreakingChange,
+}
+
+impl Default for ConventionalCommits {
+    fn default() -> ConventionalCommits {
+        ConventionalCommits {
+            types: DEFAULT_TYPES.iter().map(|s| s.to_string()).collect(),
+            scopes: None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            ParseError::MissingType => "missing type",
+            ParseError::EmptyScope => "empty scope",
+            ParseError::UnclosedScope => "scope is missing \")\"",
+            ParseError::MissingSeparator => "type must be followed by \": \"",
+            ParseError::MissingDescription => "missing description",
+            ParseError::MissingBlankLine => "missing blank line after subject",
+            ParseError::EmptyBreakingChange => "empty BREAKING CHANGE footer",
+            ParseError::LowercaseBreakingChange => "BREAKING CHANGE must be uppercase",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// Parse a commit message as a Conventional Commit.
+pub fn parse(message: &str) -> Result<ConventionalCommit<'_>, ParseError> {
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or("");
+
+    let type_end = subject
+        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(subject.len());
+    let commit_type = &subject[..type_end];
+    if commit_type.is_empty() {
+        return Err(ParseError::MissingType);
+    }
+    let mut rest = &subject[type_end..];
+
+    let mut scope = None;
+    if rest.starts_with('(') {
+        let scope_end = rest.find(')').ok_or(ParseError::UnclosedScope)?;
+        let scope_name = rest[1..scope_end].trim();
+        if scope_name.is_empty() {
+            return Err(ParseError::EmptyScope);
+        }
+        scope = Some(scope_name);
+        rest = &rest[scope_end + 1..];
+    }
+
+    let breaking_marker = rest.starts_with('!');
+    if breaking_marker {
+        rest = &rest[1..];
+    }
+
+    if !rest.starts_with(": ") {
+        return Err(ParseError::MissingSeparator);
+    }
+    let description = rest[2..].trim();
+    if description.is_empty() {
+        return Err(ParseError::MissingDescription);
+    }
+
+    match lines.next() {
+        Some(line) if !line.trim().is_empty() => return Err(ParseError::MissingBlankLine),
+        _ => {}
+    }
+
+    let mut breaking_footer = false;
+    for line in lines {
+        if let Some(text) = breaking_change_footer(line)? {
+            if text.trim().is_empty() {
+                return Err(ParseError::EmptyBreakingChange);
+            }
+            breaking_footer = true;
+        }
+    }
+
+    Ok(ConventionalCommit {
+        commit_type,
+        scope,
+        breaking: breaking_marker || breaking_footer,
+        description,
+    })
+}
+
+/// Return the text of a `BREAKING CHANGE:` footer, if the line is one.
+fn breaking_change_footer(line: &str) -> Result<Option<&str>, ParseError> {
+    for token in &["BREAKING CHANGE:", "BREAKING-CHANGE:"] {
+        if line.starts_with(token) {
+            return Ok(Some(&line[token.len()..]));
+        }
+        // Lines can start with any character, so don't slice in the middle of one.
+        if line
+            .get(..token.len())
+            .map_or(false, |head| head.eq_ignore_ascii_case(token))
+        {
+            return Err(ParseError::LowercaseBreakingChange);
+        }
+    }
+    Ok(None)
+}
+
+impl ConventionalCommits {
+    fn validate(&self, message: &str) -> Result<(), String> {
+        let commit = parse(message).map_err(|error| error.to_string())?;
+
+        if !self
+            .types
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(commit.commit_type))
+        {
+            return Err(format!("type \"{}\" is not allowed", commit.commit_type));
+        }
+
+        if let (Some(scope), Some(ref scopes)) = (commit.scope, &self.scopes) {
+            if !scopes.iter().any(|allowed| allowed.eq_ignore_ascii_case(scope)) {
+                return Err(format!("scope \"{}\" is not allowed", scope));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn hint(&self) -> String {
+        let mut hint = format!(
+            "Commit messages must follow the Conventional Commits format, like \
+             \"feat(parser)!: Add support for comments\". Allowed types: {}.",
+            self.types.join(", ")
+        );
+        if let Some(ref scopes) = self.scopes {
+            hint.push_str(&format!(" Allowed scopes: {}.", scopes.join(", ")));
+        }
+        reword_hint(&hint)
+    }
+}
+
+impl Rule for ConventionalCommits {
+    fn name(&self) -> &'static str {
+        "conventional_commits"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            // These are squashed away before merging, and have their own rule.
+            .filter(|message| !(message.starts_with("fixup! ") || message.starts_with("squash! ")))
+            .filter_map(|message| {
+                self.validate(message).err().map(|reason| {
+                    Violation::new(
+                        self,
+                        format!(
+                            "Reword \"{}\": {}",
+                            message.lines().next().unwrap_or(""),
+                            reason
+                        ),
+                        self.hint(),
+                    )
+                })
+            }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn it_parses_subjects() {
+            assert_eq!(
+                parse("feat(parser)!: Add comments"),
+                Ok(ConventionalCommit {
+                    commit_type: "feat",
+                    scope: Some("parser"),
+                    breaking: true,
+                    description: "Add comments",
+                })
+            );
+            assert_eq!(
+                parse("fix: Typo"),
+                Ok(ConventionalCommit {
+                    commit_type: "fix",
+                    scope: None,
+                    breaking: false,
+                    description: "Typo",
+                })
+            );
+        }
+
+        #[test]
+        fn it_parses_breaking_change_footers() {
+            let commit = parse("feat: Drop v1\n\nBody.\n\nBREAKING CHANGE: v1 is gone").unwrap();
+            assert!(commit.breaking);
+
+            let commit = parse("feat: Drop v1\n\nBREAKING-CHANGE: v1 is gone").unwrap();
+            assert!(commit.breaking);
+        }
+
+        #[test]
+        fn it_parses_footers_with_non_ascii_characters() {
+            let commit = parse("feat: Add åäö\n\nRéviewéd-by: Ölof Åström").unwrap();
+            assert!(!commit.breaking);
+
+            let commit = parse("feat: Add åäö\n\nBREAKING CHANGE: ölof is gone").unwrap();
+            assert!(commit.breaking);
+        }
+
+        #[test]
+        fn it_explains_invalid_messages() {
+            for (message, error) in vec![
+                ("Add stuff", ParseError::MissingSeparator),
+                (": Add stuff", ParseError::MissingType),
+                ("feat(): Add stuff", ParseError::EmptyScope),
+                ("feat(api: Add stuff", ParseError::UnclosedScope),
+                ("feat:Add stuff", ParseError::MissingSeparator),
+                ("feat: ", ParseError::MissingDescription),
+                ("feat: Add\nstuff", ParseError::MissingBlankLine),
+                ("feat: Add\n\nBREAKING CHANGE:", ParseError::EmptyBreakingChange),
+                ("feat: Add\n\nbreaking change: x", ParseError::LowercaseBreakingChange),
+            ] {
+                assert_eq!(parse(message), Err(error), "{:?}", message);
+            }
+        }
+    }
+
+    mod conventional_commits {
+        use super::*;
+
+        fn messages(rule: &ConventionalCommits, commit_messages: &[&str]) -> Vec<String> {
+            let intel = Intel {
+                commit_messages: commit_messages.iter().map(|m| m.to_string()).collect(),
+                ..Default::default()
+            };
+            rule.check(&intel).into_iter().map(|v| v.message).collect()
+        }
+
+        #[test]
+        fn it_reports_failing_commits_and_why() {
+            let rule = ConventionalCommits::default();
+            assert_eq!(
+                messages(
+                    &rule,
+                    &[
+                        "feat: Add stuff",
+                        "Add more stuff",
+                        "fixup! feat: Add stuff",
+                        "yolo: Add stuff"
+                    ]
+                ),
+                vec![
+                    "Reword \"Add more stuff\": type must be followed by \": \"",
+                    "Reword \"yolo: Add stuff\": type \"yolo\" is not allowed",
+                ]
+            );
+        }
+
+        #[test]
+        fn it_uses_configured_types_and_scopes() {
+            let rule = ConventionalCommits {
+                types: vec![String::from("feat")],
+                scopes: Some(vec![String::from("api")]),
+            };
+            assert_eq!(
+                messages(&rule, &["feat(API): Add", "feat: Add", "feat(ui): Add", "fix: Fix"]),
+                vec![
+                    "Reword \"feat(ui): Add\": scope \"ui\" is not allowed",
+                    "Reword \"fix: Fix\": type \"fix\" is not allowed",
+                ]
+            );
+            assert!(rule.hint().contains("Allowed scopes: api."));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use github_api::{CommitInfo, GitActor, User};
+
+use super::patterns::deserialize_regexes;
+use super::regex::Regex;
+use super::{Intel, Rule, Violation};
+
+/// Only allow author and committer emails from some domains, or matching some patterns.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CommitEmails {
+    /// Allowed email domains. Subdomains are allowed too.
+    pub domains: Vec<String>,
+    #[serde(deserialize_with = "deserialize_regexes")]
+    pub patterns: Vec<Regex>,
+    /// Git names or Github logins (like "dependabot[bot]") that may use any email.
+    pub exempt: Vec<String>,
+}
+
+impl CommitEmails {
+    fn is_allowed(&self, email: &str) -> bool {
+        let email = email.to_lowercase();
+        let domain = email.rsplit('@').next().unwrap_or("");
+
+        self.domains.iter().any(|allowed| {
+            let allowed = allowed.to_lowercase();
+            domain == allowed || domain.ends_with(&format!(".{}", allowed))
+        }) || self.patterns.iter().any(|pattern| pattern.is_match(&email))
+    }
+
+    fn is_exempt(&self, identity: &GitActor, account: Option<&User>) -> bool {
+        self.exempt.iter().any(|exempt| {
+            *exempt == identity.name || account.map_or(false, |user| *exempt == user.login)
+        })
+    }
+
+    /// Return a description of every identity of the commit that is not allowed.
+    fn disallowed_identities(&self, commit: &CommitInfo) -> Vec<String> {
+        let identities = vec![
+            ("author", commit.commit.author.as_ref(), commit.author.as_ref()),
+            ("committer", commit.commit.committer.as_ref(), commit.committer.as_ref()),
+        ];
+
+        identities
+            .into_iter()
+            .filter_map(|(role, identity, account)| {
+                let identity = identity?;
+                if self.is_allowed(&identity.email) || self.is_exempt(identity, account) {
+                    None
+                } else {
+                    Some(format!("{} email {}", role, identity.email))
+                }
+            }).collect()
+    }
+
+    fn policy(&self) -> String {
+        let mut allowed: Vec<String> = self
+            .domains
+            .iter()
+            .map(|domain| format!("@{}", domain))
+            .collect();
+        allowed.extend(
+            self.patterns
+                .iter()
+                .map(|pattern| format!("/{}/", pattern.as_str())),
+        );
+
+        if allowed.is_empty() {
+            String::from("No emails are allowed")
+        } else {
+            format!("Allowed emails: {}", allowed.join(", "))
+        }
+    }
+}
+
+impl Rule for CommitEmails {
+    fn name(&self) -> &'static str {
+        "commit_emails"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commits
+            .iter()
+            .filter_map(|commit| {
+                let disallowed = self.disallowed_identities(commit);
+                if disallowed.is_empty() {
+                    return None;
+                }
+
+                Some(Violation::new(
+                    self,
+                    format!(
+                        "Use an allowed email in \"{}\"",
+                        commit.commit.message.lines().next().unwrap_or("")
+                    ),
+                    format!(
+                        "Commit {} has a disallowed {}. {}. Set git config user.email and run \
+                         git rebase --exec \"git commit --amend --no-edit --reset-author\" onto \
+                         the base branch to fix every commit.",
+                        commit.sha,
+                        disallowed.join(" and "),
+                        self.policy()
+                    ),
+                ))
+            }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use github_api::Commit;
+    use judgement::toml;
+
+    fn actor(name: &str, email: &str) -> Option<GitActor> {
+        Some(GitActor {
+            name: name.into(),
+            email: email.into(),
+        })
+    }
+
+    fn commit(message: &str, author: Option<GitActor>, committer: Option<GitActor>) -> CommitInfo {
+        CommitInfo {
+            sha: String::from("abc123"),
+            commit: Commit {
+                message: message.into(),
+                author,
+                committer,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn rule() -> CommitEmails {
+        toml::from_str(
+            r#"
+            domains = ["example.com"]
+            patterns = ['^[0-9]+\+release-bot@users\.noreply\.github\.com$']
+            exempt = ["dependabot[bot]"]
+            "#,
+        ).unwrap()
+    }
+
+    #[test]
+    fn it_allows_configured_domains_and_patterns() {
+        let rule = rule();
+        assert!(rule.is_allowed("jane@example.com"));
+        assert!(rule.is_allowed("Jane@Eng.Example.com"));
+        assert!(rule.is_allowed("123+release-bot@users.noreply.github.com"));
+        assert!(!rule.is_allowed("jane@notexample.com"));
+        assert!(!rule.is_allowed("jane@users.noreply.github.com"));
+    }
+
+    #[test]
+    fn it_reports_commits_with_disallowed_emails() {
+        let jane = actor("Jane", "jane@example.com");
+        let intel = Intel {
+            commits: vec![
+                commit("Good", jane.clone(), jane.clone()),
+                commit("Personal", actor("Jane", "jane@gmail.com"), jane.clone()),
+                commit(
+                    "Both",
+                    actor("Jane", "jane@gmail.com"),
+                    actor("Jane", "123+jane@users.noreply.github.com"),
+                ),
+                commit(
+                    "Bot",
+                    actor(
+                        "dependabot[bot]",
+                        "49699333+dependabot[bot]@users.noreply.github.com",
+                    ),
+                    jane,
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let violations = rule().check(&intel);
+        let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Use an allowed email in \"Personal\"",
+                "Use an allowed email in \"Both\"",
+            ]
+        );
+        assert!(violations[1].hint.contains(
+            "disallowed author email jane@gmail.com and committer email \
+             123+jane@users.noreply.github.com"
+        ));
+        assert!(violations[1].hint.contains("Allowed emails: @example.com, /^[0-9]+"));
+    }
+
+    #[test]
+    fn it_exempts_github_accounts() {
+        let mut bot_commit = commit("Release", actor("Release", "release@bots.local"), None);
+        bot_commit.author = Some(User {
+            login: String::from("acme-release[bot]"),
+            user_type: String::from("Bot"),
+        });
+        let intel = Intel {
+            commits: vec![bot_commit],
+            ..Default::default()
+        };
+
+        let rule = CommitEmails {
+            exempt: vec![String::from("acme-release[bot]")],
+            ..Default::default()
+        };
+        assert_eq!(rule.check(&intel), vec![]);
+    }
+}
@@ -0,0 +1,562 @@
+use serde::de::{Deserialize, Deserializer, Error as DeError};
+
+use super::glob::{MatchOptions, Pattern};
+use super::{rebase_hint, Intel, Rule, Violation};
+
+const MAX_LINES: u64 = 1000;
+const MAX_FILES: u64 = 100;
+const LARGE_CHANGE_LABEL: &str = "large-change";
+const FORBIDDEN_FILES: [&str; 3] = ["*.orig", "*.rej", ".env"];
+const CHANGELOG_PATH: &str = "CHANGELOG.md";
+const NO_CHANGELOG_LABEL: &str = "no-changelog";
+
+/// Number of matching files to name in a violation.
+const LISTED_FILES: usize = 5;
+
+/// Gitignore-like pattern for paths in the repository.
+///
+/// `*` does not match `/`, but `**` does. Patterns without a `/` match the file name in any
+/// directory, and patterns that end with a `/` match everything in that directory.
+#[derive(Debug)]
+pub struct FilePattern {
+    pattern: Pattern,
+    source: String,
+    kind: PatternKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PatternKind {
+    /// Match the whole path, like `src/generated/*.rs`.
+    Path,
+    /// Match the file name in any directory, like `*.orig`.
+    FileName,
+    /// Match everything in directories with this name, like `migrations/`.
+    DirectoryName,
+}
+
+/// Fail PRs that change too many lines or files, unless they have a label that allows it.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ChangeSize {
+    pub max_lines: u64,
+    pub max_files: u64,
+    pub label: String,
+}
+
+/// Fail PRs that change some paths without a label that allows it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProtectedPaths {
+    pub paths: Vec<ProtectedPath>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProtectedPath {
+    pub path: FilePattern,
+    pub label: String,
+}
+
+/// Fail PRs that add or change files that should not be committed.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ForbiddenFiles {
+    pub patterns: Vec<FilePattern>,
+}
+
+/// Fail PRs that don't change the changelog, unless they have a label that says it is not needed.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Changelog {
+    pub path: String,
+    pub label: String,
+}
+
+impl FilePattern {
+    pub fn new(source: &str) -> Result<FilePattern, String> {
+        let trimmed = source.trim_start_matches('/').trim_end_matches('/');
+        let kind = if trimmed.contains('/') || source.starts_with('/') {
+            PatternKind::Path
+        } else if source.ends_with('/') {
+            PatternKind::DirectoryName
+        } else {
+            PatternKind::FileName
+        };
+        let glob = if kind == PatternKind::Path && source.ends_with('/') {
+            format!("{}/**", trimmed)
+        } else {
+            trimmed.to_string()
+        };
+
+        Pattern::new(&glob)
+            .map(|pattern| FilePattern {
+                pattern,
+                source: source.to_string(),
+                kind,
+            }).map_err(|error| format!("Invalid pattern \"{}\": {}", source, error.msg))
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        let matches = |part: &str| self.pattern.matches_with(part, &options);
+
+        match self.kind {
+            PatternKind::Path => matches(path),
+            PatternKind::FileName => matches(path.rsplit('/').next().unwrap_or(path)),
+            PatternKind::DirectoryName => {
+                let mut parts: Vec<&str> = path.split('/').collect();
+                parts.pop();
+                parts.into_iter().any(matches)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FilePattern {
+    fn deserialize<D>(deserializer: D) -> Result<FilePattern, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let source = String::deserialize(deserializer)?;
+        FilePattern::new(&source).map_err(D::Error::custom)
+    }
+}
+
+impl Default for ChangeSize {
+    fn default() -> ChangeSize {
+        ChangeSize {
+            max_lines: MAX_LINES,
+            max_files: MAX_FILES,
+            label: LARGE_CHANGE_LABEL.into(),
+        }
+    }
+}
+
+impl Default for ForbiddenFiles {
+    fn default() -> ForbiddenFiles {
+        ForbiddenFiles {
+            patterns: FORBIDDEN_FILES
+                .iter()
+                .map(|pattern| FilePattern::new(pattern).expect("Default patterns must be valid"))
+                .collect(),
+        }
+    }
+}
+
+impl Default for Changelog {
+    fn default() -> Changelog {
+        Changelog {
+            path: CHANGELOG_PATH.into(),
+            label: NO_CHANGELOG_LABEL.into(),
+        }
+    }
+}
+
+fn list_files(files: &[&str]) -> String {
+    let mut listing = files
+        .iter()
+        .take(LISTED_FILES)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if files.len() > LISTED_FILES {
+        listing.push_str(&format!(" and {} more", files.len() - LISTED_FILES));
+    }
+    listing
+}
+
+impl ChangeSize {
+    fn violation(&self, message: String, explanation: String) -> Violation {
+        Violation::new(
+            self,
+            message,
+            format!(
+                "{} Split it into smaller PRs that are easier to review, or add the \"{}\" label \
+                 if it cannot be split.",
+                explanation, self.label
+            ),
+        )
+    }
+}
+
+impl Rule for ChangeSize {
+    fn name(&self) -> &'static str {
+        "change_size"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        if intel.has_label(&self.label) {
+            return Vec::new();
+        }
+
+        let mut violations = Vec::new();
+        let lines: u64 = intel
+            .files
+            .iter()
+            .map(|file| file.additions + file.deletions)
+            .sum();
+        let files = intel.files.len() as u64;
+
+        if lines > self.max_lines {
+            violations.push(self.violation(
+                format!("Change at most {} lines", self.max_lines),
+                format!(
+                    "This PR changes {} lines, but at most {} are allowed.",
+                    lines, self.max_lines
+                ),
+            ));
+        }
+
+        if files > self.max_files {
+            violations.push(self.violation(
+                format!("Change at most {} files", self.max_files),
+                format!(
+                    "This PR changes {} files, but at most {} are allowed.",
+                    files, self.max_files
+                ),
+            ));
+        }
+
+        violations
+    }
+
+    fn needs_files(&self) -> bool {
+        true
+    }
+}
+
+impl Rule for ProtectedPaths {
+    fn name(&self) -> &'static str {
+        "protected_paths"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        self.paths
+            .iter()
+            .filter(|protected| !intel.has_label(&protected.label))
+            .filter_map(|protected| {
+                let changed: Vec<&str> = intel
+                    .files
+                    .iter()
+                    .filter(|file| {
+                        protected.path.matches(&file.filename)
+                            || file
+                                .previous_filename
+                                .as_ref()
+                                .map_or(false, |name| protected.path.matches(name))
+                    }).map(|file| file.filename.as_str())
+                    .collect();
+
+                if changed.is_empty() {
+                    return None;
+                }
+
+                Some(Violation::new(
+                    self,
+                    format!(
+                        "Add the \"{}\" label to change {}",
+                        protected.label, protected.path.source
+                    ),
+                    format!(
+                        "Files matching {} need extra care, and can only be changed in PRs with \
+                         the \"{}\" label. This PR changes {}.",
+                        protected.path.source,
+                        protected.label,
+                        list_files(&changed)
+                    ),
+                ))
+            }).collect()
+    }
+
+    fn needs_files(&self) -> bool {
+        true
+    }
+}
+
+impl Rule for ForbiddenFiles {
+    fn name(&self) -> &'static str {
+        "forbidden_files"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .files
+            .iter()
+            // Removing forbidden files is always fine.
+            .filter(|file| file.status != "removed")
+            .filter_map(|file| {
+                let pattern = self
+                    .patterns
+                    .iter()
+                    .find(|pattern| pattern.matches(&file.filename))?;
+
+                Some(Violation::new(
+                    self,
+                    format!("Remove \"{}\"", file.filename),
+                    rebase_hint(
+                        &format!("Files matching {} should not be committed.", pattern.source),
+                        "remove the file from the commits that add it, and consider adding it \
+                         to .gitignore",
+                    ),
+                ))
+            }).collect()
+    }
+
+    fn needs_files(&self) -> bool {
+        true
+    }
+}
+
+impl Rule for Changelog {
+    fn name(&self) -> &'static str {
+        "changelog"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        let path = self.path.trim_start_matches('/');
+        let changed = intel
+            .files
+            .iter()
+            .any(|file| file.filename == path && file.status != "removed");
+
+        if changed || intel.has_label(&self.label) {
+            return Vec::new();
+        }
+
+        vec![Violation::new(
+            self,
+            format!("Add an entry to {}", path),
+            format!(
+                "Describe the user-facing changes of this PR in {}. If there are none, add the \
+                 \"{}\" label instead.",
+                path, self.label
+            ),
+        )]
+    }
+
+    fn needs_files(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use github_api::PullRequestFile;
+    use judgement::toml;
+
+    fn file(filename: &str, status: &str, changes: u64) -> PullRequestFile {
+        PullRequestFile {
+            filename: filename.into(),
+            status: status.into(),
+            additions: changes,
+            deletions: changes,
+            previous_filename: None,
+        }
+    }
+
+    fn messages(rule: &dyn Rule, intel: &Intel) -> Vec<String> {
+        rule.check(intel).into_iter().map(|v| v.message).collect()
+    }
+
+    mod file_pattern {
+        use super::*;
+
+        fn matches(pattern: &str, path: &str) -> bool {
+            FilePattern::new(pattern).unwrap().matches(path)
+        }
+
+        #[test]
+        fn it_matches_file_names_in_any_directory() {
+            assert!(matches("*.orig", "src/main.rs.orig"));
+            assert!(matches(".env", ".env"));
+            assert!(matches(".env", "config/.env"));
+            assert!(!matches(".env", ".env.example"));
+        }
+
+        #[test]
+        fn it_matches_directories() {
+            assert!(matches("migrations/", "migrations/001_init.sql"));
+            assert!(matches("migrations/", "db/migrations/001_init.sql"));
+            assert!(matches("db/migrations/", "db/migrations/001_init.sql"));
+            assert!(!matches("db/migrations/", "other/db/migrations/001_init.sql"));
+            assert!(!matches("migrations/", "src/migrations.rs"));
+        }
+
+        #[test]
+        fn it_matches_paths() {
+            assert!(matches("src/generated/*.rs", "src/generated/api.rs"));
+            assert!(!matches("src/generated/*.rs", "src/generated/v1/api.rs"));
+            assert!(matches("src/generated/**/*.rs", "src/generated/v1/api.rs"));
+            assert!(matches("/Cargo.lock", "Cargo.lock"));
+        }
+
+        #[test]
+        fn it_rejects_invalid_patterns() {
+            assert!(FilePattern::new("src/[").is_err());
+        }
+    }
+
+    mod change_size {
+        use super::*;
+
+        #[test]
+        fn it_limits_lines_and_files() {
+            let rule = ChangeSize {
+                max_lines: 10,
+                max_files: 1,
+                ..Default::default()
+            };
+            let intel = Intel {
+                files: vec![file("a.rs", "modified", 3), file("b.rs", "added", 3)],
+                ..Default::default()
+            };
+
+            assert_eq!(
+                messages(&rule, &intel),
+                vec!["Change at most 10 lines", "Change at most 1 files"]
+            );
+        }
+
+        #[test]
+        fn it_allows_large_changes_with_label() {
+            let rule = ChangeSize {
+                max_lines: 1,
+                ..Default::default()
+            };
+            let intel = Intel {
+                label_names: vec!["Large change"],
+                files: vec![file("a.rs", "modified", 3)],
+                ..Default::default()
+            };
+
+            assert!(messages(&rule, &intel).is_empty());
+        }
+    }
+
+    mod protected_paths {
+        use super::*;
+
+        fn rule() -> ProtectedPaths {
+            toml::from_str(
+                r#"
+                [[paths]]
+                path = "migrations/"
+                label = "migration"
+
+                [[paths]]
+                path = "src/generated/**"
+                label = "regenerated"
+                "#,
+            ).unwrap()
+        }
+
+        #[test]
+        fn it_requires_labels_to_change_protected_paths() {
+            let mut renamed = file("src/api.rs", "renamed", 1);
+            renamed.previous_filename = Some(String::from("src/generated/api.rs"));
+            let intel = Intel {
+                files: vec![
+                    file("migrations/001.sql", "added", 10),
+                    file("src/main.rs", "modified", 1),
+                    renamed,
+                ],
+                label_names: vec!["Migration"],
+                ..Default::default()
+            };
+
+            let violations = rule().check(&intel);
+            assert_eq!(violations.len(), 1);
+            assert_eq!(
+                violations[0].message,
+                "Add the \"regenerated\" label to change src/generated/**"
+            );
+            assert!(violations[0].hint.ends_with("This PR changes src/api.rs."));
+        }
+
+        #[test]
+        fn it_lists_a_few_changed_files() {
+            let files: Vec<&str> = vec!["a", "b", "c", "d", "e", "f", "g"];
+            assert_eq!(list_files(&files), "a, b, c, d, e and 2 more");
+        }
+    }
+
+    mod changelog {
+        use super::*;
+
+        #[test]
+        fn it_requires_a_changelog_entry() {
+            let intel = Intel {
+                files: vec![file("src/main.rs", "modified", 1)],
+                ..Default::default()
+            };
+            assert_eq!(
+                messages(&Changelog::default(), &intel),
+                vec!["Add an entry to CHANGELOG.md"]
+            );
+
+            let intel = Intel {
+                files: vec![
+                    file("src/main.rs", "modified", 1),
+                    file("CHANGELOG.md", "modified", 1),
+                ],
+                ..Default::default()
+            };
+            assert!(messages(&Changelog::default(), &intel).is_empty());
+        }
+
+        #[test]
+        fn it_uses_configured_path_and_label() {
+            let rule = Changelog {
+                path: String::from("/docs/Changelog.md"),
+                label: String::from("skip changelog"),
+            };
+
+            let intel = Intel {
+                files: vec![file("docs/Changelog.md", "modified", 1)],
+                ..Default::default()
+            };
+            assert!(messages(&rule, &intel).is_empty());
+
+            let intel = Intel {
+                label_names: vec!["Skip-Changelog"],
+                files: vec![file("CHANGELOG.md", "modified", 1)],
+                ..Default::default()
+            };
+            assert!(messages(&rule, &intel).is_empty());
+
+            let intel = Intel {
+                label_names: vec!["no-changelog"],
+                ..Default::default()
+            };
+            assert_eq!(
+                messages(&rule, &intel),
+                vec!["Add an entry to docs/Changelog.md"]
+            );
+        }
+    }
+
+    mod forbidden_files {
+        use super::*;
+
+        #[test]
+        fn it_forbids_adding_matching_files() {
+            let intel = Intel {
+                files: vec![
+                    file("src/main.rs", "modified", 1),
+                    file("src/main.rs.orig", "added", 1),
+                    file(".env", "removed", 1),
+                ],
+                ..Default::default()
+            };
+
+            assert_eq!(
+                messages(&ForbiddenFiles::default(), &intel),
+                vec!["Remove \"src/main.rs.orig\""]
+            );
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use super::{reword_hint, Intel, Rule, Violation};
+
+const MAX_SUBJECT_LENGTH: usize = 72;
+const MAX_BODY_WIDTH: usize = 72;
+
+/// Subjects are shortened to this many characters when quoted in a violation.
+const QUOTED_SUBJECT_LENGTH: usize = 50;
+
+/// Verbs that are commonly used in the wrong form at the start of a subject, like "Added" or
+/// "Fixes" instead of "Add" and "Fix".
+const COMMON_VERBS: [&str; 40] = [
+    "add", "allow", "avoid", "bump", "change", "clean", "convert", "correct", "create", "delete",
+    "disable", "document", "drop", "enable", "ensure", "extract", "fix", "handle", "hide",
+    "implement", "improve", "introduce", "make", "merge", "move", "prevent", "refactor",
+    "release", "remove", "rename", "replace", "revert", "set", "show", "simplify", "split",
+    "support", "update", "upgrade", "use",
+];
+
+/// Fail commits with long subjects.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SubjectMaxLength {
+    pub max: usize,
+}
+
+/// Fail commits with subjects that end with a period.
+#[derive(Debug, Default, Deserialize)]
+pub struct SubjectTrailingPeriod {}
+
+/// Fail commits with subjects that start with a common verb in the wrong form, like "Added" or
+/// "Fixes". This is only a heuristic.
+#[derive(Debug, Default, Deserialize)]
+pub struct ImperativeSubject {}
+
+/// Fail commits where the subject is not followed by a blank line.
+#[derive(Debug, Default, Deserialize)]
+pub struct BlankLineAfterSubject {}
+
+/// Fail commits with long lines in the body. Lines without spaces (like URLs) are allowed.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BodyMaxWidth {
+    pub max: usize,
+}
+
+impl Default for SubjectMaxLength {
+    fn default() -> SubjectMaxLength {
+        SubjectMaxLength {
+            max: MAX_SUBJECT_LENGTH,
+        }
+    }
+}
+
+impl Default for BodyMaxWidth {
+    fn default() -> BodyMaxWidth {
+        BodyMaxWidth {
+            max: MAX_BODY_WIDTH,
+        }
+    }
+}
+
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("")
+}
+
+fn quoted_subject(message: &str) -> String {
+    let subject = subject(message);
+    match subject.char_indices().nth(QUOTED_SUBJECT_LENGTH) {
+        Some((index, _)) => format!("{}...", &subject[..index]),
+        None => subject.to_string(),
+    }
+}
+
+/// Find verb forms like "Added", "Adds", or "Adding" and return the imperative form.
+fn non_imperative_verb(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    COMMON_VERBS.iter().cloned().find(|verb| {
+        let stem = verb.trim_end_matches('e');
+        let last = &verb[verb.len() - 1..];
+        [
+            format!("{}s", verb),
+            format!("{}es", verb),
+            format!("{}ed", stem),
+            format!("{}ing", stem),
+            format!("{}{}ed", verb, last),
+            format!("{}{}ing", verb, last),
+        ].contains(&word)
+    })
+}
+
+impl Rule for SubjectMaxLength {
+    fn name(&self) -> &'static str {
+        "subject_max_length"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            .filter(|message| subject(message).chars().count() > self.max)
+            .map(|message| {
+                Violation::new(
+                    self,
+                    format!("Shorten the subject of \"{}\"", quoted_subject(message)),
+                    reword_hint(&format!(
+                        "Subjects can be at most {} characters long. Move details to the body of \
+                         the message.",
+                        self.max
+                    )),
+                )
+            }).collect()
+    }
+}
+
+impl Rule for SubjectTrailingPeriod {
+    fn name(&self) -> &'static str {
+        "subject_trailing_period"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            .filter(|message| {
+                let subject = subject(message).trim_end();
+                subject.ends_with('.') && !subject.ends_with("...")
+            }).map(|message| {
+                Violation::new(
+                    self,
+                    format!("Remove the period from \"{}\"", quoted_subject(message)),
+                    reword_hint("Subjects are titles, and should not end with a period."),
+                )
+            }).collect()
+    }
+}
+
+impl Rule for ImperativeSubject {
+    fn name(&self) -> &'static str {
+        "imperative_subject"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            .filter_map(|message| {
+                let subject = subject(message);
+                // Skip prefixes like "feat(parser): " or "docs: ".
+                let description = match subject.find(": ") {
+                    Some(index) if !subject[..index].contains(' ') => &subject[index + 2..],
+                    _ => subject,
+                };
+                let first_word = description.split_whitespace().next()?;
+                let verb = non_imperative_verb(first_word)?;
+
+                Some(Violation::new(
+                    self,
+                    format!("Use imperative mood in \"{}\"", quoted_subject(message)),
+                    reword_hint(&format!(
+                        "Write subjects as commands, like \"{}\" instead of \"{}\". A good subject \
+                         completes the sentence \"If applied, this commit will ...\".",
+                        verb, first_word
+                    )),
+                ))
+            }).collect()
+    }
+}
+
+impl Rule for BlankLineAfterSubject {
+    fn name(&self) -> &'static str {
+        "blank_line_after_subject"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            .filter(|message| match message.lines().nth(1) {
+                Some(line) => !line.trim().is_empty(),
+                None => false,
+            }).map(|message| {
+                Violation::new(
+                    self,
+                    format!("Add a blank line after \"{}\"", quoted_subject(message)),
+                    reword_hint(
+                        "The subject must be separated from the body with a blank line, or git \
+                         will treat the whole paragraph as the subject.",
+                    ),
+                )
+            }).collect()
+    }
+}
+
+impl Rule for BodyMaxWidth {
+    fn name(&self) -> &'static str {
+        "body_max_width"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commit_messages
+            .iter()
+            .filter(|message| {
+                message.lines().skip(1).any(|line| {
+                    line.chars().count() > self.max && line.trim().contains(char::is_whitespace)
+                })
+            }).map(|message| {
+                Violation::new(
+                    self,
+                    format!("Wrap the body of \"{}\"", quoted_subject(message)),
+                    reword_hint(&format!(
+                        "Lines in the body can be at most {} characters long.",
+                        self.max
+                    )),
+                )
+            }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(rule: &dyn Rule, commit_messages: &[&str]) -> Vec<String> {
+        let intel = Intel {
+            commit_messages: commit_messages.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+        rule.check(&intel).into_iter().map(|v| v.message).collect()
+    }
+
+    #[test]
+    fn it_forbids_long_subjects() {
+        let rule = SubjectMaxLength { max: 10 };
+        assert_eq!(
+            messages(&rule, &["Add thing", "Add another thing\n\nShort body"]),
+            vec!["Shorten the subject of \"Add another thing\""]
+        );
+    }
+
+    #[test]
+    fn it_shortens_quoted_subjects() {
+        let long = "Å".repeat(60);
+        assert_eq!(quoted_subject(&long), format!("{}...", "Å".repeat(50)));
+        assert_eq!(quoted_subject("Short\n\nBody"), "Short");
+    }
+
+    #[test]
+    fn it_forbids_trailing_periods() {
+        assert_eq!(
+            messages(
+                &SubjectTrailingPeriod {},
+                &["Add thing.", "Add thing", "Wait for it...", "Add thing\n\nBody."]
+            ),
+            vec!["Remove the period from \"Add thing.\""]
+        );
+    }
+
+    #[test]
+    fn it_detects_non_imperative_verbs() {
+        assert_eq!(non_imperative_verb("Added"), Some("add"));
+        assert_eq!(non_imperative_verb("fixes"), Some("fix"));
+        assert_eq!(non_imperative_verb("Removing"), Some("remove"));
+        assert_eq!(non_imperative_verb("Updated"), Some("update"));
+        assert_eq!(non_imperative_verb("dropped"), Some("drop"));
+        assert_eq!(non_imperative_verb("Add"), None);
+        assert_eq!(non_imperative_verb("Addition"), None);
+        assert_eq!(non_imperative_verb("Process"), None);
+    }
+
+    #[test]
+    fn it_requires_imperative_subjects() {
+        assert_eq!(
+            messages(
+                &ImperativeSubject {},
+                &["Added thing", "feat(api): Adds endpoint", "Fix bug", "Fixing: the bug"]
+            ),
+            vec![
+                "Use imperative mood in \"Added thing\"",
+                "Use imperative mood in \"feat(api): Adds endpoint\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_requires_blank_line_after_subject() {
+        assert_eq!(
+            messages(
+                &BlankLineAfterSubject {},
+                &["Add thing\nMore text", "Add thing\n\nBody", "Add thing"]
+            ),
+            vec!["Add a blank line after \"Add thing\""]
+        );
+    }
+
+    #[test]
+    fn it_forbids_wide_bodies() {
+        let rule = BodyMaxWidth { max: 20 };
+        assert_eq!(
+            messages(
+                &rule,
+                &[
+                    "Add wide\n\nThis line is a lot wider than twenty",
+                    "Add link\n\nhttps://example.com/a/very/long/link",
+                    "Add narrow\n\nThis line is fine",
+                ]
+            ),
+            vec!["Wrap the body of \"Add wide\""]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Intel, Rule, Violation};
+
+/// Fail PRs that contain merge commits, for repositories that keep a linear history.
+#[derive(Debug, Default, Deserialize)]
+pub struct MergeCommits {}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+impl Rule for MergeCommits {
+    fn name(&self) -> &'static str {
+        "merge_commits"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        let merges: Vec<_> = intel
+            .commits
+            .iter()
+            .filter(|commit| commit.parents.len() > 1)
+            .collect();
+
+        if merges.is_empty() {
+            return Vec::new();
+        }
+
+        let short_shas: Vec<&str> = merges.iter().map(|commit| short_sha(&commit.sha)).collect();
+        let listing: Vec<String> = merges
+            .iter()
+            .map(|commit| {
+                format!(
+                    "{} (\"{}\")",
+                    commit.sha,
+                    commit.commit.message.lines().next().unwrap_or("")
+                )
+            }).collect();
+
+        vec![Violation::new(
+            self,
+            format!(
+                "Rebase onto the base branch instead of merging it ({})",
+                short_shas.join(", ")
+            ),
+            format!(
+                "This repository keeps a linear history, so PRs cannot contain merge commits. \
+                 Merge commits in this PR: {}. Drop them with git rebase onto the base branch.",
+                listing.join(", ")
+            ),
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use github_api::{Commit, CommitInfo, CommitRef};
+
+    fn commit(sha: &str, message: &str, parents: &[&str]) -> CommitInfo {
+        CommitInfo {
+            sha: sha.into(),
+            commit: Commit {
+                message: message.into(),
+                ..Default::default()
+            },
+            parents: parents
+                .iter()
+                .map(|sha| CommitRef { sha: sha.to_string() })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_allows_linear_history() {
+        let intel = Intel {
+            commits: vec![commit("bbbbbbbbbb", "Add feature", &["aaaaaaaaaa"])],
+            ..Default::default()
+        };
+        assert_eq!(MergeCommits {}.check(&intel), vec![]);
+    }
+
+    #[test]
+    fn it_names_merge_commits() {
+        let intel = Intel {
+            commits: vec![
+                commit("bbbbbbbbbb", "Add feature", &["aaaaaaaaaa"]),
+                commit(
+                    "cccccccccc",
+                    "Merge branch 'master' into feature\n\nConflicts: none",
+                    &["bbbbbbbbbb", "0000000000"],
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let violations = MergeCommits {}.check(&intel);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "Rebase onto the base branch instead of merging it (ccccccc)"
+        );
+        assert!(
+            violations[0]
+                .hint
+                .contains("cccccccccc (\"Merge branch 'master' into feature\")")
+        );
+    }
+}
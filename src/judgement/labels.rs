@@ -0,0 +1,322 @@
+use super::{normalize_label, Intel, Rule, Violation};
+
+const MAGIC_IGNORE_LABEL: &str = "prgnome ignore";
+
+const FORBIDDEN_LABELS: [&str; 8] = [
+    "work in progress",
+    "wip",
+    "in progress",
+    "dont merge",
+    "do not merge",
+    "wait",
+    "not ready",
+    "blocked",
+];
+
+/// Fail PRs with labels that mark them as not ready to be merged.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ForbiddenLabels {
+    pub labels: Vec<String>,
+}
+
+/// Fail PRs that don't have a label from each group.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RequiredLabels {
+    pub groups: Vec<Vec<String>>,
+}
+
+/// Fail PRs that have more than one label from any group.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ExclusiveLabels {
+    pub groups: Vec<Vec<String>>,
+}
+
+/// Approve PRs with this label, whatever the other rules find.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct IgnoreLabel {
+    pub label: String,
+}
+
+impl Default for ForbiddenLabels {
+    fn default() -> ForbiddenLabels {
+        ForbiddenLabels {
+            labels: FORBIDDEN_LABELS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for IgnoreLabel {
+    fn default() -> IgnoreLabel {
+        IgnoreLabel {
+            label: MAGIC_IGNORE_LABEL.to_string(),
+        }
+    }
+}
+
+/// Whether a label matches a label from the config. Config labels that end with a `*` match every
+/// label that starts with them, like "area:*".
+fn label_matches(pattern: &str, name: &str) -> bool {
+    let name = normalize_label(name);
+    if pattern.ends_with('*') {
+        let prefix = normalize_label(pattern.trim_end_matches('*'));
+        name == prefix || name.starts_with(&format!("{} ", prefix))
+    } else {
+        name == normalize_label(pattern)
+    }
+}
+
+/// Labels of the PR that are in the group.
+fn labels_in_group<'a>(intel: &Intel<'a>, group: &[String]) -> Vec<&'a str> {
+    intel
+        .label_names
+        .iter()
+        .filter(|name| group.iter().any(|pattern| label_matches(pattern, name)))
+        .cloned()
+        .collect()
+}
+
+/// List labels like: "a", "b" or "c".
+fn quote_labels<S: AsRef<str>>(labels: &[S], conjunction: &str) -> String {
+    let quoted: Vec<String> = labels
+        .iter()
+        .map(|label| format!("\"{}\"", label.as_ref()))
+        .collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{} {} {}", rest.join(", "), conjunction, last)
+        }
+        _ => quoted.join(""),
+    }
+}
+
+impl Rule for ForbiddenLabels {
+    fn name(&self) -> &'static str {
+        "forbidden_labels"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .label_names
+            .iter()
+            .filter(|name| {
+                let normalized = normalize_label(name);
+                self.labels
+                    .iter()
+                    .any(|forbidden| normalize_label(forbidden) == normalized)
+            }).map(|name| {
+                Violation::new(
+                    self,
+                    format!("Remove the \"{}\" label", name),
+                    format!(
+                        "The \"{}\" label marks this PR as not ready to be merged. Remove the \
+                         label once it is ready.",
+                        name
+                    ),
+                )
+            }).collect()
+    }
+}
+
+impl Rule for RequiredLabels {
+    fn name(&self) -> &'static str {
+        "required_labels"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        self.groups
+            .iter()
+            .filter(|group| !group.is_empty() && labels_in_group(intel, group).is_empty())
+            .map(|group| {
+                let message = if group.len() == 1 {
+                    format!("Add the {} label", quote_labels(group, "or"))
+                } else {
+                    format!("Add one of the {} labels", quote_labels(group, "or"))
+                };
+                Violation::new(
+                    self,
+                    message,
+                    "Every PR must be labeled so it can be triaged. Labels ending with * stand \
+                     for every label that starts the same way.",
+                )
+            }).collect()
+    }
+}
+
+impl Rule for ExclusiveLabels {
+    fn name(&self) -> &'static str {
+        "exclusive_labels"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        self.groups
+            .iter()
+            .filter_map(|group| {
+                let present = labels_in_group(intel, group);
+                if present.len() < 2 {
+                    return None;
+                }
+
+                Some(Violation::new(
+                    self,
+                    format!("Keep only one of the {} labels", quote_labels(&present, "and")),
+                    format!(
+                        "A PR can only have one of the {} labels.",
+                        quote_labels(group, "or")
+                    ),
+                ))
+            }).collect()
+    }
+}
+
+impl Rule for IgnoreLabel {
+    fn name(&self) -> &'static str {
+        "ignore_label"
+    }
+
+    fn check(&self, _intel: &Intel) -> Vec<Violation> {
+        Vec::new()
+    }
+
+    fn force_approval(&self, intel: &Intel) -> Option<String> {
+        let ignore_label = normalize_label(&self.label);
+        intel
+            .label_names
+            .iter()
+            .find(|name| normalize_label(name) == ignore_label)
+            .map(|name| format!("Tagged with {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_has_a_valid_whitelist() {
+        // Whitelist must be normalized already
+        for label in FORBIDDEN_LABELS.iter() {
+            assert_eq!(&normalize_label(label), label);
+        }
+    }
+
+    #[test]
+    fn it_forbids_intel_with_forbidden_labels() {
+        let intel = Intel {
+            label_names: vec!["do NOT merge!!!!!"],
+            ..Default::default()
+        };
+
+        let violations = ForbiddenLabels::default().check(&intel);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "Remove the \"do NOT merge!!!!!\" label");
+    }
+
+    #[test]
+    fn it_uses_configured_labels() {
+        let intel = Intel {
+            label_names: vec!["needs review", "wip"],
+            ..Default::default()
+        };
+        let rule = ForbiddenLabels {
+            labels: vec![String::from("Needs-Review")],
+        };
+
+        let violations = rule.check(&intel);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "Remove the \"needs review\" label");
+    }
+
+    #[test]
+    fn it_matches_labels_with_wildcards() {
+        assert!(label_matches("type: bug", "Type/Bug"));
+        assert!(label_matches("area:*", "area: ui"));
+        assert!(label_matches("area:*", "Area/Backend"));
+        assert!(!label_matches("area:*", "areas"));
+        assert!(!label_matches("type: bug", "type: bugfix"));
+    }
+
+    #[test]
+    fn it_requires_a_label_from_each_group() {
+        let rule = RequiredLabels {
+            groups: vec![
+                vec![
+                    String::from("type: bug"),
+                    String::from("type: feature"),
+                    String::from("type: chore"),
+                ],
+                vec![String::from("area:*")],
+            ],
+        };
+
+        let intel = Intel {
+            label_names: vec!["Type/Bug"],
+            ..Default::default()
+        };
+        let violations = rule.check(&intel);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "Add the \"area:*\" label");
+
+        let intel = Intel {
+            label_names: vec!["area/ui"],
+            ..Default::default()
+        };
+        let violations = rule.check(&intel);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "Add one of the \"type: bug\", \"type: feature\" or \"type: chore\" labels"
+        );
+    }
+
+    #[test]
+    fn it_forbids_more_than_one_label_from_a_group() {
+        let rule = ExclusiveLabels {
+            groups: vec![vec![
+                String::from("type: bug"),
+                String::from("type: feature"),
+                String::from("type: chore"),
+            ]],
+        };
+
+        let intel = Intel {
+            label_names: vec!["type: bug", "area: ui", "Type/Feature"],
+            ..Default::default()
+        };
+        let violations = rule.check(&intel);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "Keep only one of the \"type: bug\" and \"Type/Feature\" labels"
+        );
+
+        let intel = Intel {
+            label_names: vec!["type: bug", "area: ui"],
+            ..Default::default()
+        };
+        assert_eq!(rule.check(&intel), vec![]);
+    }
+
+    #[test]
+    fn it_force_approves_with_the_ignore_label() {
+        let rule = IgnoreLabel::default();
+
+        let intel = Intel {
+            label_names: vec!["wip", "Prgnome-Ignore"],
+            ..Default::default()
+        };
+        assert_eq!(
+            rule.force_approval(&intel),
+            Some(String::from("Tagged with Prgnome-Ignore"))
+        );
+
+        let intel = Intel {
+            label_names: vec!["wip"],
+            ..Default::default()
+        };
+        assert_eq!(rule.force_approval(&intel), None);
+    }
+}
@@ -0,0 +1,625 @@
+extern crate glob;
+extern crate regex;
+extern crate toml;
+
+mod commits;
+mod conventional;
+mod emails;
+mod files;
+mod format;
+mod history;
+mod labels;
+mod overrides;
+mod patterns;
+mod references;
+mod signatures;
+mod signoff;
+
+use serde::de::{DeserializeOwned, Error as DeError};
+use std::fmt;
+use std::sync::Arc;
+
+use github_api::{CommitInfo, PullRequestFile};
+
+pub use self::commits::{ForbiddenIntros, ForbiddenMessages, MaxCommits};
+pub use self::conventional::ConventionalCommits;
+pub use self::emails::CommitEmails;
+pub use self::files::{ChangeSize, Changelog, ForbiddenFiles, ProtectedPaths};
+pub use self::format::{
+    BlankLineAfterSubject, BodyMaxWidth, ImperativeSubject, SubjectMaxLength,
+    SubjectTrailingPeriod,
+};
+pub use self::history::MergeCommits;
+pub use self::labels::{ExclusiveLabels, ForbiddenLabels, IgnoreLabel, RequiredLabels};
+pub use self::overrides::{Override, RejectedOverride};
+pub use self::patterns::MessagePatterns;
+pub use self::references::IssueReference;
+pub use self::signatures::VerifiedCommits;
+pub use self::signoff::SignedOffBy;
+
+#[derive(Debug, Default)]
+pub struct Intel<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    pub label_names: Vec<&'a str>,
+    pub total_commits: u64,
+    pub commit_messages: Vec<String>,
+    /// Every commit in the PR, for rules that need more than the message.
+    pub commits: Vec<CommitInfo>,
+    /// Files changed by the PR. Only loaded if a rule needs them.
+    pub files: Vec<PullRequestFile>,
+    /// Override labels on the PR that waive some rules.
+    pub overrides: Vec<Override>,
+    /// Override labels on the PR that were applied by someone who may not.
+    pub rejected_overrides: Vec<RejectedOverride>,
+}
+
+/// A single problem with a PR.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    /// Name of the rule that found the problem.
+    pub rule: String,
+    /// Short description of the problem, that fits in a status.
+    pub message: String,
+    /// Explanation of the problem and how to fix it.
+    pub hint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Judgement {
+    Approved,
+    NotApproved { violations: Vec<Violation> },
+    ForceApproved(String),
+    /// Approved because override labels waive every problem.
+    Overridden {
+        labels: Vec<String>,
+        waived: Vec<Violation>,
+    },
+}
+
+/// A check that PRs are judged by.
+///
+/// Rules are configured in the `[rules.<name>]` table of the config, which is deserialized into
+/// the rule. Every rule can also be turned on or off with an `enabled` key in that table.
+pub trait Rule: fmt::Debug + Send + Sync {
+    /// Name of the rule, as used in the config.
+    fn name(&self) -> &'static str;
+
+    /// Return every problem this rule finds with the PR.
+    fn check(&self, intel: &Intel) -> Vec<Violation>;
+
+    /// Rules can approve a PR regardless of what the other rules find. Return the reason to do
+    /// so if this rule does.
+    fn force_approval(&self, _intel: &Intel) -> Option<String> {
+        None
+    }
+
+    /// Whether this rule looks at `Intel::files`. Files are only loaded from the Github API if
+    /// an enabled rule needs them.
+    fn needs_files(&self) -> bool {
+        false
+    }
+}
+
+/// The enabled rules, in the order that their violations are reported.
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<Arc<dyn Rule>>,
+}
+
+struct RuleDefinition {
+    name: &'static str,
+    enabled_by_default: bool,
+    build: fn(toml::Value) -> Result<Arc<dyn Rule>, toml::de::Error>,
+}
+
+/// Every rule that can be enabled in the config.
+const BUILT_IN_RULES: &[RuleDefinition] = &[
+    RuleDefinition {
+        name: "max_commits",
+        enabled_by_default: true,
+        build: build_rule::<MaxCommits>,
+    },
+    RuleDefinition {
+        name: "forbidden_messages",
+        enabled_by_default: true,
+        build: build_rule::<ForbiddenMessages>,
+    },
+    RuleDefinition {
+        name: "forbidden_intros",
+        enabled_by_default: true,
+        build: build_rule::<ForbiddenIntros>,
+    },
+    RuleDefinition {
+        name: "message_patterns",
+        enabled_by_default: true,
+        build: build_rule::<MessagePatterns>,
+    },
+    RuleDefinition {
+        name: "conventional_commits",
+        enabled_by_default: false,
+        build: build_rule::<ConventionalCommits>,
+    },
+    RuleDefinition {
+        name: "subject_max_length",
+        enabled_by_default: false,
+        build: build_rule::<SubjectMaxLength>,
+    },
+    RuleDefinition {
+        name: "subject_trailing_period",
+        enabled_by_default: false,
+        build: build_rule::<SubjectTrailingPeriod>,
+    },
+    RuleDefinition {
+        name: "imperative_subject",
+        enabled_by_default: false,
+        build: build_rule::<ImperativeSubject>,
+    },
+    RuleDefinition {
+        name: "blank_line_after_subject",
+        enabled_by_default: false,
+        build: build_rule::<BlankLineAfterSubject>,
+    },
+    RuleDefinition {
+        name: "body_max_width",
+        enabled_by_default: false,
+        build: build_rule::<BodyMaxWidth>,
+    },
+    RuleDefinition {
+        name: "merge_commits",
+        enabled_by_default: false,
+        build: build_rule::<MergeCommits>,
+    },
+    RuleDefinition {
+        name: "signed_off_by",
+        enabled_by_default: false,
+        build: build_rule::<SignedOffBy>,
+    },
+    RuleDefinition {
+        name: "verified_commits",
+        enabled_by_default: false,
+        build: build_rule::<VerifiedCommits>,
+    },
+    RuleDefinition {
+        name: "commit_emails",
+        enabled_by_default: false,
+        build: build_rule::<CommitEmails>,
+    },
+    RuleDefinition {
+        name: "issue_reference",
+        enabled_by_default: false,
+        build: build_rule::<IssueReference>,
+    },
+    RuleDefinition {
+        name: "change_size",
+        enabled_by_default: false,
+        build: build_rule::<ChangeSize>,
+    },
+    RuleDefinition {
+        name: "protected_paths",
+        enabled_by_default: false,
+        build: build_rule::<ProtectedPaths>,
+    },
+    RuleDefinition {
+        name: "forbidden_files",
+        enabled_by_default: false,
+        build: build_rule::<ForbiddenFiles>,
+    },
+    RuleDefinition {
+        name: "changelog",
+        enabled_by_default: false,
+        build: build_rule::<Changelog>,
+    },
+    RuleDefinition {
+        name: "forbidden_labels",
+        enabled_by_default: true,
+        build: build_rule::<ForbiddenLabels>,
+    },
+    RuleDefinition {
+        name: "required_labels",
+        enabled_by_default: false,
+        build: build_rule::<RequiredLabels>,
+    },
+    RuleDefinition {
+        name: "exclusive_labels",
+        enabled_by_default: false,
+        build: build_rule::<ExclusiveLabels>,
+    },
+    RuleDefinition {
+        name: "ignore_label",
+        enabled_by_default: true,
+        build: build_rule::<IgnoreLabel>,
+    },
+];
+
+fn build_rule<R>(config: toml::Value) -> Result<Arc<dyn Rule>, toml::de::Error>
+where
+    R: Rule + DeserializeOwned + 'static,
+{
+    let rule: R = config.try_into()?;
+    Ok(Arc::new(rule))
+}
+
+/// Whether a rule with this name exists.
+pub fn is_rule_name(name: &str) -> bool {
+    BUILT_IN_RULES.iter().any(|rule| rule.name == name)
+}
+
+/// Hint for problems that are fixed by rewriting the commits of the PR, like "squash them".
+fn rebase_hint(explanation: &str, fix: &str) -> String {
+    format!(
+        "{} Use an interactive rebase (git rebase -i) onto the base branch to {}.",
+        explanation, fix
+    )
+}
+
+/// Hint for problems with commit messages.
+fn reword_hint(explanation: &str) -> String {
+    rebase_hint(explanation, "reword the commit")
+}
+
+impl Violation {
+    pub fn new<M, H>(rule: &dyn Rule, message: M, hint: H) -> Violation
+    where
+        M: Into<String>,
+        H: Into<String>,
+    {
+        Violation {
+            rule: rule.name().into(),
+            message: message.into(),
+            hint: hint.into(),
+        }
+    }
+}
+
+impl RuleSet {
+    /// Build a rule set from the `rules` table of the config. Rules that are not mentioned get
+    /// their default config.
+    pub fn from_config(config: &toml::value::Table) -> Result<RuleSet, toml::de::Error> {
+        if let Some(unknown) = config
+            .keys()
+            .find(|name| !BUILT_IN_RULES.iter().any(|rule| rule.name == name.as_str()))
+        {
+            return Err(toml::de::Error::custom(format!("Unknown rule \"{}\"", unknown)));
+        }
+
+        let mut rules = Vec::new();
+        for definition in BUILT_IN_RULES {
+            let rule_config = config
+                .get(definition.name)
+                .cloned()
+                .unwrap_or_else(|| toml::Value::Table(Default::default()));
+
+            let enabled = match rule_config.get("enabled") {
+                Some(value) => value.as_bool().ok_or_else(|| {
+                    toml::de::Error::custom(format!(
+                        "rules.{}.enabled must be true or false",
+                        definition.name
+                    ))
+                })?,
+                None => definition.enabled_by_default,
+            };
+
+            if enabled {
+                let rule = (definition.build)(rule_config).map_err(|error| {
+                    toml::de::Error::custom(format!("rules.{}: {}", definition.name, error))
+                })?;
+                rules.push(rule);
+            }
+        }
+
+        Ok(RuleSet { rules })
+    }
+
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    pub fn needs_files(&self) -> bool {
+        self.rules.iter().any(|rule| rule.needs_files())
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> RuleSet {
+        RuleSet::from_config(&Default::default()).expect("Default rules must be valid")
+    }
+}
+
+impl<'a> Intel<'a> {
+    /// Whether the PR has the label, ignoring case and punctuation.
+    pub fn has_label(&self, name: &str) -> bool {
+        let normalized = normalize_label(name);
+        self.label_names
+            .iter()
+            .any(|label| normalize_label(label) == normalized)
+    }
+
+    /// Every problem the rules find, even if the PR is force approved or the problems are
+    /// waived.
+    pub fn violations(&self, rules: &RuleSet) -> Vec<Violation> {
+        rules.rules.iter().flat_map(|rule| rule.check(self)).collect()
+    }
+
+    /// Labels on the PR that force approve it on their own.
+    pub fn force_approving_labels(&self, rules: &RuleSet) -> Vec<&'a str> {
+        self.label_names
+            .iter()
+            .cloned()
+            .filter(|name| {
+                let labeled = Intel {
+                    label_names: vec![name],
+                    ..Default::default()
+                };
+                rules
+                    .rules
+                    .iter()
+                    .any(|rule| rule.force_approval(&labeled).is_some())
+            }).collect()
+    }
+
+    pub fn validate(&self, rules: &RuleSet) -> Judgement {
+        if let Some(reason) = rules
+            .rules
+            .iter()
+            .filter_map(|rule| rule.force_approval(self))
+            .next()
+        {
+            return Judgement::ForceApproved(reason);
+        }
+
+        let mut violations = self.violations(rules);
+        violations.extend(self.rejected_overrides.iter().map(|r| r.violation()));
+
+        let (waived, violations): (Vec<Violation>, Vec<Violation>) = violations
+            .into_iter()
+            .partition(|violation| self.overrides.iter().any(|o| o.waives(violation)));
+
+        if !violations.is_empty() {
+            Judgement::NotApproved { violations }
+        } else if !waived.is_empty() {
+            let labels = self
+                .overrides
+                .iter()
+                .filter(|o| waived.iter().any(|violation| o.waives(violation)))
+                .map(|o| o.label.clone())
+                .collect();
+            Judgement::Overridden { labels, waived }
+        } else {
+            Judgement::Approved
+        }
+    }
+}
+
+pub fn normalize_label(name: &str) -> String {
+    let normalized: String = name
+        .chars()
+        .flat_map(|c: char| match c {
+            val if val.is_alphanumeric() => Some(val.to_ascii_lowercase()),
+            ' ' | '-' | '/' | ':' => Some(' '),
+            _ => None,
+        })
+        .collect();
+
+    // "type: bug" and "Type/Bug" should be the same label.
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(config: &str) -> Result<RuleSet, toml::de::Error> {
+        let table: toml::value::Table = toml::from_str(config).unwrap();
+        RuleSet::from_config(&table)
+    }
+
+    fn messages(judgement: &Judgement) -> Vec<String> {
+        match judgement {
+            Judgement::NotApproved { violations } => {
+                violations.iter().map(|v| v.message.clone()).collect()
+            }
+            other => panic!("Expected a NotApproved judgement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_normalizes_labels() {
+        for (input, output) in &[
+            ("Hello world", "hello world"),
+            ("size:large", "size large"),
+            ("look/at/mEeE!?", "look at meee"),
+            ("type: bug", "type bug"),
+            ("Type/Bug", "type bug"),
+            (" - Do not merge - ", "do not merge"),
+        ] {
+            assert_eq!(&normalize_label(input), output);
+        }
+    }
+
+    #[test]
+    fn it_enables_default_rules() {
+        assert_eq!(
+            RuleSet::default().rule_names(),
+            vec![
+                "max_commits",
+                "forbidden_messages",
+                "forbidden_intros",
+                "message_patterns",
+                "forbidden_labels",
+                "ignore_label",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_configures_rules_by_name() {
+        let rules = rules(
+            r#"
+            [max_commits]
+            max = 1
+
+            [forbidden_labels]
+            enabled = false
+            "#,
+        ).unwrap();
+        assert_eq!(
+            rules.rule_names(),
+            vec![
+                "max_commits",
+                "forbidden_messages",
+                "forbidden_intros",
+                "message_patterns",
+                "ignore_label",
+            ]
+        );
+
+        let intel = Intel {
+            total_commits: 2,
+            label_names: vec!["wip"],
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(&intel.validate(&rules)),
+            vec!["Rebase until you have 1 commits or fewer"]
+        );
+    }
+
+    #[test]
+    fn it_leaves_opt_in_rules_disabled() {
+        let rules = rules("[conventional_commits]\nenabled = true").unwrap();
+        assert!(rules.rule_names().contains(&"conventional_commits"));
+        assert!(!RuleSet::default().rule_names().contains(&"conventional_commits"));
+    }
+
+    #[test]
+    fn it_rejects_invalid_rule_config() {
+        assert!(rules("[not_a_rule]").is_err());
+        assert!(rules("[max_commits]\nenabled = \"yes\"").is_err());
+        assert!(rules("[max_commits]\nmax = \"many\"").is_err());
+    }
+
+    #[test]
+    fn it_allows_empty_intel() {
+        let intel = Intel::default();
+        assert_eq!(intel.validate(&RuleSet::default()), Judgement::Approved);
+    }
+
+    #[test]
+    fn it_records_multiple_violations() {
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("Initial commit"),
+                String::from("fixup! Initial commit"),
+            ],
+            label_names: vec!["Work-in-progress"],
+            ..Default::default()
+        };
+
+        match intel.validate(&RuleSet::default()) {
+            Judgement::NotApproved { violations } => {
+                let found: Vec<(&str, &str)> = violations
+                    .iter()
+                    .map(|v| (v.rule.as_str(), v.message.as_str()))
+                    .collect();
+                assert_eq!(
+                    found,
+                    vec![
+                        ("forbidden_intros", "Rebase away \"fixup! Initial commit\""),
+                        ("forbidden_labels", "Remove the \"Work-in-progress\" label"),
+                    ]
+                );
+            }
+            other => panic!("Expected a NotApproved judgement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_waives_rules_with_override_labels() {
+        let allow_fixup = Override {
+            label: String::from("prgnome: allow-fixup"),
+            rules: vec![String::from("forbidden_intros")],
+        };
+        let allow_large = Override {
+            label: String::from("prgnome: allow-large"),
+            rules: vec![String::from("max_commits")],
+        };
+        let intel = Intel {
+            commit_messages: vec![String::from("fixup! Initial commit")],
+            overrides: vec![allow_fixup.clone(), allow_large],
+            ..Default::default()
+        };
+
+        match intel.validate(&RuleSet::default()) {
+            Judgement::Overridden { labels, waived } => {
+                assert_eq!(labels, vec!["prgnome: allow-fixup"]);
+                assert_eq!(waived.len(), 1);
+                assert_eq!(waived[0].rule, "forbidden_intros");
+            }
+            other => panic!("Expected an Overridden judgement, got {:?}", other),
+        }
+
+        let intel = Intel {
+            commit_messages: vec![String::from("fixup! Initial commit")],
+            label_names: vec!["wip"],
+            overrides: vec![allow_fixup],
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(&intel.validate(&RuleSet::default())),
+            vec!["Remove the \"wip\" label"]
+        );
+    }
+
+    #[test]
+    fn it_fails_on_rejected_overrides() {
+        let intel = Intel {
+            rejected_overrides: vec![RejectedOverride {
+                label: String::from("prgnome: allow-fixup"),
+                actor: Some(String::from("octocat")),
+                comment: false,
+            }],
+            ..Default::default()
+        };
+
+        match intel.validate(&RuleSet::default()) {
+            Judgement::NotApproved { violations } => assert_eq!(violations[0].rule, "overrides"),
+            other => panic!("Expected a NotApproved judgement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_approves_all_issues_if_tagged_with_magic_label() {
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("Initial commit"),
+                String::from("fixup! Initial commit"),
+            ],
+            label_names: vec!["Work-in-progress", "prgnome-ignore"],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            intel.validate(&RuleSet::default()),
+            Judgement::ForceApproved(String::from("Tagged with prgnome-ignore")),
+        );
+    }
+
+    #[test]
+    fn it_finds_labels_that_force_approve() {
+        let intel = Intel {
+            label_names: vec!["bug", "Prgnome Ignore", "wip"],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            intel.force_approving_labels(&RuleSet::default()),
+            vec!["Prgnome Ignore"]
+        );
+        assert_eq!(
+            intel.force_approving_labels(&rules("[ignore_label]\nenabled = false").unwrap()),
+            Vec::<&str>::new()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::Violation;
+
+/// Rule name used for problems with override labels themselves.
+const OVERRIDES_RULE: &str = "overrides";
+
+/// An override label on the PR, and the rules it waives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub label: String,
+    pub rules: Vec<String>,
+}
+
+/// An override label that was applied by someone who may not apply it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedOverride {
+    pub label: String,
+    /// Login of the person who applied the label, if it could be found.
+    pub actor: Option<String>,
+    /// Whether the override was a `/prgnome ignore` command in a comment, and not a label.
+    pub comment: bool,
+}
+
+impl Override {
+    pub fn waives(&self, violation: &Violation) -> bool {
+        self.rules.contains(&violation.rule)
+    }
+}
+
+impl RejectedOverride {
+    pub fn violation(&self) -> Violation {
+        let applied_by = match self.actor {
+            Some(ref actor) => format!("@{}", actor),
+            None => String::from("someone unknown"),
+        };
+
+        if self.comment {
+            return Violation {
+                rule: OVERRIDES_RULE.into(),
+                message: format!(
+                    "\"{}\" was commented by {}, who may not override rules",
+                    self.label, applied_by
+                ),
+                hint: format!(
+                    "Only some people may override rules in this repository. Delete the comment, \
+                     and ask someone who may override rules to comment \"{}\" instead.",
+                    self.label
+                ),
+            };
+        }
+
+        Violation {
+            rule: OVERRIDES_RULE.into(),
+            message: format!(
+                "\"{}\" was applied by {}, who may not override rules",
+                self.label, applied_by
+            ),
+            hint: format!(
+                "Only some people may apply override labels in this repository. Remove the \"{}\" \
+                 label, and ask someone who may override rules to apply it again.",
+                self.label
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_waives_violations_of_its_rules() {
+        let allow_fixup = Override {
+            label: String::from("prgnome: allow-fixup"),
+            rules: vec![String::from("forbidden_intros")],
+        };
+        let violation = |rule: &str| Violation {
+            rule: rule.into(),
+            message: String::new(),
+            hint: String::new(),
+        };
+
+        assert!(allow_fixup.waives(&violation("forbidden_intros")));
+        assert!(!allow_fixup.waives(&violation("max_commits")));
+    }
+
+    #[test]
+    fn it_explains_rejected_overrides() {
+        let rejected = RejectedOverride {
+            label: String::from("prgnome: allow-large"),
+            actor: Some(String::from("octocat")),
+            comment: false,
+        };
+        assert_eq!(
+            rejected.violation().message,
+            "\"prgnome: allow-large\" was applied by @octocat, who may not override rules"
+        );
+
+        let rejected = RejectedOverride {
+            label: String::from("/prgnome ignore max_commits"),
+            actor: Some(String::from("octocat")),
+            comment: true,
+        };
+        assert_eq!(
+            rejected.violation().message,
+            "\"/prgnome ignore max_commits\" was commented by @octocat, who may not override rules"
+        );
+    }
+}
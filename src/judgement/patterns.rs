@@ -0,0 +1,253 @@
+use super::regex::Regex;
+use serde::de::{Deserialize, Deserializer, Error as DeError};
+
+use super::{reword_hint, Intel, Rule, Violation};
+
+/// Check commit messages against regular expressions.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MessagePatterns {
+    pub patterns: Vec<MessagePattern>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessagePattern {
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub pattern: Regex,
+    /// Whether every message must match the pattern, or no message may match it.
+    #[serde(default)]
+    pub mode: PatternMode,
+    /// Part of the commit message to match the pattern against.
+    #[serde(default)]
+    pub applies_to: MessagePart,
+    /// Short description of the problem, shown in the status.
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternMode {
+    Forbid,
+    Require,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessagePart {
+    Subject,
+    Body,
+    Message,
+}
+
+pub fn deserialize_regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(D::Error::custom)
+}
+
+pub fn deserialize_regexes<'de, D>(deserializer: D) -> Result<Vec<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(D::Error::custom))
+        .collect()
+}
+
+impl Default for PatternMode {
+    fn default() -> PatternMode {
+        PatternMode::Forbid
+    }
+}
+
+impl Default for MessagePart {
+    fn default() -> MessagePart {
+        MessagePart::Subject
+    }
+}
+
+impl MessagePart {
+    fn extract(self, message: &str) -> &str {
+        match self {
+            MessagePart::Subject => message.lines().next().unwrap_or(""),
+            MessagePart::Body => match message.find('\n') {
+                Some(index) => message[index..].trim_start_matches(&['\r', '\n'][..]),
+                None => "",
+            },
+            MessagePart::Message => message,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            MessagePart::Subject => "subject",
+            MessagePart::Body => "body",
+            MessagePart::Message => "message",
+        }
+    }
+}
+
+impl MessagePattern {
+    fn is_violated_by(&self, message: &str) -> bool {
+        let is_match = self.pattern.is_match(self.applies_to.extract(message));
+        match self.mode {
+            PatternMode::Forbid => is_match,
+            PatternMode::Require => !is_match,
+        }
+    }
+
+    fn hint(&self) -> String {
+        match self.hint {
+            Some(ref hint) => hint.clone(),
+            None => reword_hint(&format!(
+                "The {part} of every commit {must} match /{pattern}/.",
+                part = self.applies_to.name(),
+                must = match self.mode {
+                    PatternMode::Forbid => "must not",
+                    PatternMode::Require => "must",
+                },
+                pattern = self.pattern.as_str(),
+            )),
+        }
+    }
+}
+
+impl Rule for MessagePatterns {
+    fn name(&self) -> &'static str {
+        "message_patterns"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for message in &intel.commit_messages {
+            let subject = MessagePart::Subject.extract(message);
+
+            for pattern in &self.patterns {
+                if pattern.is_violated_by(message) {
+                    violations.push(Violation::new(
+                        self,
+                        format!("{}: \"{}\"", pattern.message, subject),
+                        pattern.hint(),
+                    ));
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use judgement::toml;
+
+    fn rule(config: &str) -> Result<MessagePatterns, toml::de::Error> {
+        toml::from_str(config)
+    }
+
+    fn messages(rule: &MessagePatterns, commit_messages: &[&str]) -> Vec<String> {
+        let intel = Intel {
+            commit_messages: commit_messages.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+        rule.check(&intel).into_iter().map(|v| v.message).collect()
+    }
+
+    #[test]
+    fn it_extracts_message_parts() {
+        let message = "Subject\n\nFirst line\nSecond line";
+        assert_eq!(MessagePart::Subject.extract(message), "Subject");
+        assert_eq!(MessagePart::Body.extract(message), "First line\nSecond line");
+        assert_eq!(MessagePart::Message.extract(message), message);
+        assert_eq!(MessagePart::Body.extract("Subject"), "");
+    }
+
+    #[test]
+    fn it_forbids_matching_subjects() {
+        let rule = rule(
+            r#"
+            [[patterns]]
+            pattern = "^Merge branch"
+            message = "Rebase instead of merging"
+            "#,
+        ).unwrap();
+
+        assert_eq!(
+            messages(&rule, &["Merge branch 'master'", "Add feature\n\nMerge branch later"]),
+            vec!["Rebase instead of merging: \"Merge branch 'master'\""]
+        );
+    }
+
+    #[test]
+    fn it_requires_matching_subjects() {
+        let rule = rule(
+            r#"
+            [[patterns]]
+            pattern = '^(feat|fix|chore)(\(.+\))?: '
+            mode = "require"
+            message = "Use a conventional subject"
+            "#,
+        ).unwrap();
+
+        assert_eq!(
+            messages(&rule, &["feat(api): Add endpoint", "fix: Typo", "Add stuff"]),
+            vec!["Use a conventional subject: \"Add stuff\""]
+        );
+    }
+
+    #[test]
+    fn it_matches_bodies_and_whole_messages() {
+        let rule = rule(
+            r#"
+            [[patterns]]
+            pattern = "(?i)^Change-Id:"
+            applies_to = "body"
+            message = "Remove the Change-Id"
+            hint = "We don't use Gerrit here."
+
+            [[patterns]]
+            pattern = "(?m)^Jira: [A-Z]+-[0-9]+$"
+            applies_to = "message"
+            mode = "require"
+            message = "Reference a Jira issue"
+            "#,
+        ).unwrap();
+
+        let intel = Intel {
+            commit_messages: vec![
+                String::from("Change-Id: in subject is fine\n\nJira: ABC-1"),
+                String::from("Add feature\n\nchange-id: I1234"),
+            ],
+            ..Default::default()
+        };
+        let violations = rule.check(&intel);
+        let found: Vec<(&str, &str)> = violations
+            .iter()
+            .map(|v| (v.message.as_str(), v.hint.as_str()))
+            .collect();
+
+        assert_eq!(
+            found[0],
+            (
+                "Remove the Change-Id: \"Add feature\"",
+                "We don't use Gerrit here."
+            )
+        );
+        assert_eq!(found[1].0, "Reference a Jira issue: \"Add feature\"");
+        assert!(found[1].1.contains("The message of every commit must match"));
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn it_rejects_invalid_patterns() {
+        assert!(rule("[[patterns]]\npattern = \"(\"\nmessage = \"Nope\"").is_err());
+        assert!(rule("[[patterns]]\npattern = \"x\"").is_err());
+        assert!(rule("[[patterns]]\npattern = \"x\"\nmessage = \"x\"\nmode = \"maybe\"").is_err());
+    }
+}
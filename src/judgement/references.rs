@@ -0,0 +1,207 @@
+use super::patterns::deserialize_regex;
+use super::regex::Regex;
+use super::{Intel, Rule, Violation};
+
+/// Jira keys like "PROJ-123", or Github references like "#123".
+const DEFAULT_PATTERN: &str = r"\b[A-Z][A-Z0-9_]+-[0-9]+\b|#[0-9]+\b";
+const DEFAULT_FORMAT: &str = "a Jira key like PROJ-123, or a Github issue like #123";
+
+/// Require the PR or its commits to reference an issue or ticket.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct IssueReference {
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub pattern: Regex,
+    /// Human description of the pattern, used to explain failures.
+    pub format: String,
+    pub scope: ReferenceScope,
+}
+
+/// Where the reference must be.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceScope {
+    Title,
+    Body,
+    TitleOrBody,
+    AnyCommit,
+    EveryCommit,
+}
+
+impl Default for IssueReference {
+    fn default() -> IssueReference {
+        IssueReference {
+            pattern: Regex::new(DEFAULT_PATTERN).expect("Default pattern must be valid"),
+            format: DEFAULT_FORMAT.into(),
+            scope: ReferenceScope::default(),
+        }
+    }
+}
+
+impl Default for ReferenceScope {
+    fn default() -> ReferenceScope {
+        ReferenceScope::TitleOrBody
+    }
+}
+
+impl IssueReference {
+    fn violation(&self, message: String, location: &str) -> Violation {
+        Violation::new(
+            self,
+            message,
+            format!(
+                "{} must mention {} (matching /{}/).",
+                location,
+                self.format,
+                self.pattern.as_str()
+            ),
+        )
+    }
+}
+
+impl Rule for IssueReference {
+    fn name(&self) -> &'static str {
+        "issue_reference"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        let references = |text: &str| self.pattern.is_match(text);
+
+        match self.scope {
+            ReferenceScope::Title if !references(intel.title) => vec![self.violation(
+                String::from("Reference an issue in the PR title"),
+                "The title of the PR",
+            )],
+            ReferenceScope::Body if !references(intel.body) => vec![self.violation(
+                String::from("Reference an issue in the PR description"),
+                "The description of the PR",
+            )],
+            ReferenceScope::TitleOrBody if !references(intel.title) && !references(intel.body) => {
+                vec![self.violation(
+                    String::from("Reference an issue in the PR title or description"),
+                    "The title or description of the PR",
+                )]
+            }
+            ReferenceScope::AnyCommit
+                if !intel.commit_messages.iter().any(|message| references(message)) =>
+            {
+                vec![self.violation(
+                    String::from("Reference an issue in a commit message"),
+                    "At least one commit message",
+                )]
+            }
+            ReferenceScope::EveryCommit => intel
+                .commit_messages
+                .iter()
+                .filter(|message| !references(message))
+                .map(|message| {
+                    self.violation(
+                        format!(
+                            "Reference an issue in \"{}\"",
+                            message.lines().next().unwrap_or("")
+                        ),
+                        "Every commit message",
+                    )
+                }).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use judgement::toml;
+
+    fn messages(rule: &IssueReference, intel: &Intel) -> Vec<String> {
+        rule.check(intel).into_iter().map(|v| v.message).collect()
+    }
+
+    fn rule(scope: ReferenceScope) -> IssueReference {
+        IssueReference {
+            scope,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_matches_jira_keys_and_github_issues_by_default() {
+        let rule = IssueReference::default();
+        for text in &["PROJ-123: Fix", "Fixes #12", "See acme/widgets#3", "AB2-1"] {
+            assert!(rule.pattern.is_match(text), "{:?}", text);
+        }
+        for text in &["Fix bug", "proj-123", "#hashtag"] {
+            assert!(!rule.pattern.is_match(text), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn it_checks_title_and_body() {
+        let intel = Intel {
+            title: "Fix bug",
+            body: "Fixes #12",
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(&rule(ReferenceScope::Title), &intel),
+            vec!["Reference an issue in the PR title"]
+        );
+        assert!(messages(&rule(ReferenceScope::Body), &intel).is_empty());
+        assert!(messages(&rule(ReferenceScope::TitleOrBody), &intel).is_empty());
+
+        let intel = Intel {
+            title: "Fix bug",
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(&rule(ReferenceScope::TitleOrBody), &intel),
+            vec!["Reference an issue in the PR title or description"]
+        );
+    }
+
+    #[test]
+    fn it_checks_commits() {
+        let intel = Intel {
+            title: "PROJ-1: Fix bug",
+            commit_messages: vec![
+                String::from("Fix bug\n\nFixes PROJ-1"),
+                String::from("Add test"),
+            ],
+            ..Default::default()
+        };
+
+        assert!(messages(&rule(ReferenceScope::AnyCommit), &intel).is_empty());
+        assert_eq!(
+            messages(&rule(ReferenceScope::EveryCommit), &intel),
+            vec!["Reference an issue in \"Add test\""]
+        );
+
+        let intel = Intel {
+            commit_messages: vec![String::from("Add test")],
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(&rule(ReferenceScope::AnyCommit), &intel),
+            vec!["Reference an issue in a commit message"]
+        );
+    }
+
+    #[test]
+    fn it_explains_the_expected_format() {
+        let rule: IssueReference = toml::from_str(
+            r#"
+            pattern = '\bWID-[0-9]+\b'
+            format = "a Widget ticket like WID-1"
+            scope = "title"
+            "#,
+        ).unwrap();
+
+        let violations = rule.check(&Intel::default());
+        assert_eq!(
+            violations[0].hint,
+            "The title of the PR must mention a Widget ticket like WID-1 \
+             (matching /\\bWID-[0-9]+\\b/)."
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use super::{Intel, Rule, Violation};
+
+/// Require every commit to have a signature that Github could verify.
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifiedCommits {}
+
+/// Explain the reasons Github gives for not verifying a signature.
+fn describe_reason(reason: &str) -> &str {
+    match reason {
+        "unsigned" => "the commit is not signed",
+        "unknown_key" => "the key is not registered on any Github account",
+        "expired_key" => "the key has expired",
+        "not_signing_key" => "the key is not allowed to sign",
+        "bad_email" | "unverified_email" | "no_user" => {
+            "the committer email is not a verified email of the key's Github account"
+        }
+        "unknown_signature_type" | "malformed_signature" => "the signature could not be read",
+        "invalid" => "the signature is invalid",
+        "gpgverify_error" | "gpgverify_unavailable" => {
+            "Github could not check the signature, try again later"
+        }
+        other => other,
+    }
+}
+
+impl Rule for VerifiedCommits {
+    fn name(&self) -> &'static str {
+        "verified_commits"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commits
+            .iter()
+            .filter_map(|commit| {
+                let reason = match commit.commit.verification {
+                    Some(ref verification) if verification.verified => return None,
+                    Some(ref verification) => verification.reason.as_str(),
+                    None => "unknown",
+                };
+
+                Some(Violation::new(
+                    self,
+                    format!(
+                        "Sign \"{}\" ({})",
+                        commit.commit.message.lines().next().unwrap_or(""),
+                        reason
+                    ),
+                    format!(
+                        "Every commit must have a verified signature, but {} for commit {}. Set \
+                         up commit signing with GPG or SSH, add the key to your Github account, \
+                         and run git rebase --exec \"git commit --amend --no-edit -S\" onto the \
+                         base branch to sign every commit.",
+                        describe_reason(reason),
+                        commit.sha
+                    ),
+                ))
+            }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use github_api::{Commit, CommitInfo, Verification};
+
+    fn commit(message: &str, verification: Option<(bool, &str)>) -> CommitInfo {
+        CommitInfo {
+            sha: String::from("abc123"),
+            commit: Commit {
+                message: message.into(),
+                verification: verification.map(|(verified, reason)| Verification {
+                    verified,
+                    reason: reason.into(),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_reports_unverified_commits_with_reason() {
+        let intel = Intel {
+            commits: vec![
+                commit("Signed", Some((true, "valid"))),
+                commit("Unsigned", Some((false, "unsigned"))),
+                commit("Unknown key", Some((false, "unknown_key"))),
+                commit("No verification", None),
+            ],
+            ..Default::default()
+        };
+
+        let violations = VerifiedCommits {}.check(&intel);
+        let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Sign \"Unsigned\" (unsigned)",
+                "Sign \"Unknown key\" (unknown_key)",
+                "Sign \"No verification\" (unknown)",
+            ]
+        );
+        assert!(
+            violations[1]
+                .hint
+                .contains("the key is not registered on any Github account for commit abc123")
+        );
+    }
+}
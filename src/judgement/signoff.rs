@@ -0,0 +1,217 @@
+use github_api::{CommitInfo, GitActor};
+
+use super::{Intel, Rule, Violation};
+
+const SIGN_OFF_TRAILER: &str = "Signed-off-by";
+
+/// Require every commit to be signed off by its author, as required by the Developer Certificate
+/// of Origin (https://developercertificate.org/).
+#[derive(Debug, Default, Deserialize)]
+pub struct SignedOffBy {}
+
+/// Parse the git trailers (like "Signed-off-by: Name <email>") in the last paragraph of a commit
+/// message. The subject is never a trailer.
+pub fn trailers(message: &str) -> Vec<(&str, &str)> {
+    let body = match message.find('\n') {
+        Some(index) => &message[index..],
+        None => return Vec::new(),
+    };
+
+    let last_paragraph = body
+        .trim_end()
+        .rsplit("\n\n")
+        .next()
+        .unwrap_or("")
+        .trim_start_matches('\n');
+
+    last_paragraph
+        .lines()
+        .filter_map(|line| {
+            let separator = line.find(':')?;
+            let token = line[..separator].trim();
+            if token.is_empty() || token.contains(char::is_whitespace) {
+                return None;
+            }
+            Some((token, line[separator + 1..].trim()))
+        }).collect()
+}
+
+/// Split "Name <email>" into its parts.
+fn parse_identity(value: &str) -> Option<(&str, &str)> {
+    let start = value.find('<')?;
+    let end = value.rfind('>')?;
+    if end < start {
+        return None;
+    }
+    Some((value[..start].trim(), value[start + 1..end].trim()))
+}
+
+fn is_signed_off_by(message: &str, author: &GitActor) -> bool {
+    trailers(message)
+        .into_iter()
+        .filter(|(token, _)| token.eq_ignore_ascii_case(SIGN_OFF_TRAILER))
+        .filter_map(|(_, value)| parse_identity(value))
+        .any(|(name, email)| name == author.name && email.eq_ignore_ascii_case(&author.email))
+}
+
+fn subject(commit: &CommitInfo) -> &str {
+    commit.commit.message.lines().next().unwrap_or("")
+}
+
+impl Rule for SignedOffBy {
+    fn name(&self) -> &'static str {
+        "signed_off_by"
+    }
+
+    fn check(&self, intel: &Intel) -> Vec<Violation> {
+        intel
+            .commits
+            .iter()
+            .filter_map(|commit| {
+                let problem = match commit.commit.author {
+                    Some(ref author) if is_signed_off_by(&commit.commit.message, author) => {
+                        return None;
+                    }
+                    Some(ref author) => format!(
+                        "Commit {} needs a \"{}: {} <{}>\" trailer.",
+                        commit.sha, SIGN_OFF_TRAILER, author.name, author.email
+                    ),
+                    // A DCO check must not pass commits it can't check.
+                    None => format!(
+                        "The author of commit {} is unknown, so it can't be signed off by them. \
+                         Commit it again with an author and a \"{}\" trailer.",
+                        commit.sha, SIGN_OFF_TRAILER
+                    ),
+                };
+
+                Some(Violation::new(
+                    self,
+                    format!("Sign off \"{}\"", subject(commit)),
+                    format!(
+                        "Every commit must be signed off by its author to certify the Developer \
+                         Certificate of Origin. {} Run git rebase --signoff onto the base branch \
+                         to sign off every commit.",
+                        problem
+                    ),
+                ))
+            }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use github_api::Commit;
+
+    fn commit(message: &str) -> CommitInfo {
+        CommitInfo {
+            sha: String::from("abc123"),
+            commit: Commit {
+                message: message.into(),
+                author: Some(GitActor {
+                    name: String::from("Jane Doe"),
+                    email: String::from("jane@example.com"),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    mod trailers {
+        use super::*;
+
+        #[test]
+        fn it_parses_trailers_in_the_last_paragraph() {
+            assert_eq!(
+                trailers(
+                    "Add thing\n\nSee: the body\n\nSigned-off-by: Jane <jane@example.com>\n\
+                     Reviewed-by: John <john@example.com>\n"
+                ),
+                vec![
+                    ("Signed-off-by", "Jane <jane@example.com>"),
+                    ("Reviewed-by", "John <john@example.com>"),
+                ]
+            );
+        }
+
+        #[test]
+        fn it_ignores_the_subject_and_prose() {
+            assert_eq!(trailers("fix: Add thing"), vec![]);
+            assert_eq!(trailers("Add thing\n\nThis is some prose: really"), vec![]);
+        }
+    }
+
+    mod signed_off_by {
+        use super::*;
+
+        fn messages(commits: Vec<CommitInfo>) -> Vec<String> {
+            let intel = Intel {
+                commits,
+                ..Default::default()
+            };
+            SignedOffBy {}
+                .check(&intel)
+                .into_iter()
+                .map(|v| v.message)
+                .collect()
+        }
+
+        #[test]
+        fn it_accepts_sign_offs_by_the_author() {
+            assert_eq!(
+                messages(vec![
+                    commit("Add thing\n\nSigned-off-by: Jane Doe <jane@example.com>"),
+                    commit("Add other\n\nBody\n\nsigned-off-by: Jane Doe <JANE@example.com>"),
+                ]),
+                Vec::<String>::new()
+            );
+        }
+
+        #[test]
+        fn it_lists_commits_without_matching_sign_off() {
+            assert_eq!(
+                messages(vec![
+                    commit("Add thing"),
+                    commit("Add other\n\nSigned-off-by: John Doe <john@example.com>"),
+                    commit("Add third\n\nSigned-off-by: Jane Doe <jane@example.com>"),
+                ]),
+                vec!["Sign off \"Add thing\"", "Sign off \"Add other\""]
+            );
+        }
+
+        #[test]
+        fn it_explains_the_expected_trailer() {
+            let intel = Intel {
+                commits: vec![commit("Add thing")],
+                ..Default::default()
+            };
+            let violations = SignedOffBy {}.check(&intel);
+            assert!(
+                violations[0]
+                    .hint
+                    .contains("\"Signed-off-by: Jane Doe <jane@example.com>\"")
+            );
+        }
+
+        #[test]
+        fn it_reports_commits_with_unknown_authors() {
+            let mut unknown = commit("Add thing\n\nSigned-off-by: Jane Doe <jane@example.com>");
+            unknown.commit.author = None;
+            let intel = Intel {
+                commits: vec![unknown],
+                ..Default::default()
+            };
+
+            let violations = SignedOffBy {}.check(&intel);
+
+            assert_eq!(violations.len(), 1);
+            assert_eq!(violations[0].message, "Sign off \"Add thing\"");
+            assert!(
+                violations[0]
+                    .hint
+                    .contains("The author of commit abc123 is unknown")
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
 extern crate actix_web;
+extern crate chrono;
 extern crate crypto;
 extern crate dotenv;
 extern crate env_logger;
@@ -27,16 +28,26 @@ use listenfd::ListenFd;
 use std::sync::Arc;
 use structopt::StructOpt;
 
+mod audit_log;
+mod comment;
+mod config;
 mod event;
 mod github_api;
 mod judgement;
 mod options;
+mod pipeline;
+mod reevaluation_queue;
+mod replay;
+mod report_store;
 mod server;
+mod slash_command;
 mod token_store;
 mod utils;
 
+use audit_log::AuditLog;
+use config::ConfigSource;
 use github_api::Client as GithubClient;
-use options::AppOptions;
+use options::{AppOptions, Command};
 use server::ServerState;
 use utils::log_error_trace;
 
@@ -46,7 +57,20 @@ fn main() {
     let app_options = AppOptions::from_args();
     app_options.init_logger();
 
-    match run(app_options) {
+    let result = match app_options.command {
+        Some(Command::Replay(ref replay_options)) => {
+            server_config(&app_options).and_then(|server_config| {
+                replay::run(replay_options, &server_config, || api_client(&app_options))
+            })
+        }
+        Some(Command::Audit(ref audit_options)) => audit_log::run(
+            audit_options,
+            app_options.audit_log_path.as_ref().map(|path| path.as_path()),
+        ),
+        None => run(app_options),
+    };
+
+    match result {
         Ok(_) => {}
         Err(err) => {
             log_error_trace(err.as_fail());
@@ -57,9 +81,23 @@ fn main() {
 
 fn run(app_options: AppOptions) -> Result<(), Error> {
     let api_client = api_client(&app_options).context("Could not initialize Github API")?;
+    let webhook_secret = match app_options.github_webhook_secret {
+        Some(ref secret) => secret,
+        None => bail!("No webhook secret is configured. Set it with --github-webhook-secret."),
+    };
+    let config = server_config(&app_options)?;
+    let resolved_config = config.config()?;
+    let audit_log = match app_options.audit_log_path {
+        Some(ref path) => Some(AuditLog::open(path).context("Could not open audit log")?),
+        None => None,
+    };
     let state = Arc::new(ServerState::new(
         api_client,
-        &app_options.github_webhook_secret,
+        webhook_secret,
+        config,
+        resolved_config,
+        audit_log,
+        app_options.admin_token.clone(),
     ));
 
     let mut listenfd = ListenFd::from_env();
@@ -70,6 +108,12 @@ fn run(app_options: AppOptions) -> Result<(), Error> {
             .resource("/webhook", |r| {
                 r.method(http::Method::POST).with(server::handle_webhook)
             })
+            .resource("/report/{owner}/{repo}/{sha}", |r| {
+                r.method(http::Method::GET).f(server::handle_report)
+            })
+            .resource("/admin/reevaluate/{owner}/{repo}", |r| {
+                r.method(http::Method::POST).f(server::handle_reevaluate)
+            })
     })
     .keep_alive(actix_web::server::KeepAlive::Disabled);
 
@@ -85,8 +129,24 @@ fn run(app_options: AppOptions) -> Result<(), Error> {
 }
 
 fn api_client(app_options: &AppOptions) -> Result<GithubClient, Error> {
+    let app_id = match app_options.github_app_id {
+        Some(app_id) => app_id,
+        None => bail!("No Github App ID is configured. Set it with --github-app-id."),
+    };
     let private_key =
         ::std::fs::read(&app_options.private_key_path).context("Failed to load private key")?;
 
-    Ok(GithubClient::new(app_options.github_app_id, private_key))
+    Ok(GithubClient::new(app_id, private_key))
+}
+
+fn server_config(app_options: &AppOptions) -> Result<ConfigSource, Error> {
+    let source = match app_options.config_path {
+        Some(ref path) => ConfigSource::read(path).context("Failed to load config")?,
+        None => ConfigSource::default(),
+    };
+
+    // Fail early on invalid config rather than on the first webhook.
+    source.config().context("Invalid config")?;
+
+    Ok(source)
 }
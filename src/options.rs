@@ -1,5 +1,6 @@
 extern crate log;
 
+use chrono::{DateTime, Utc};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -18,26 +19,28 @@ pub struct AppOptions {
     /// The Github APP ID. You can determine the app if by looking at the Github Settings panel for
     /// the app you created in order to install this program to your repo or organization.
     ///
-    /// It is commonly a smallish integer, like 123456.
+    /// It is commonly a smallish integer, like 123456. It is required to run the server, and to
+    /// use the Github API from other commands.
     ///
     #[structopt(
         long = "github-app-id",
         env = "GITHUB_APP_ID",
         value_name = "ID",
     )]
-    pub github_app_id: u64,
+    pub github_app_id: Option<u64>,
 
     /// The Github webhook secret. You should have gotten this when you created the app to install
     /// this program to your repo or organization.
     ///
-    /// If you've lost it, you may set a new one in the settings panel for the app on Github.
+    /// If you've lost it, you may set a new one in the settings panel for the app on Github. It is
+    /// required to run the server.
     ///
     #[structopt(
         long = "github-webhook-secret",
         env = "GITHUB_WEBHOOK_SECRET",
         value_name = "SECRET",
     )]
-    pub github_webhook_secret: String,
+    pub github_webhook_secret: Option<String>,
 
     /// Path to the Github App private key file (in DER format).
     ///
@@ -54,6 +57,40 @@ pub struct AppOptions {
     )]
     pub private_key_path: PathBuf,
 
+    /// Path to a config file (in TOML format) with the default config for all repositories.
+    ///
+    /// Repositories can override parts of it by placing a .github/prgnome.toml file on the base
+    /// branch of their PRs. Built-in defaults are used for everything that is not configured.
+    ///
+    #[structopt(
+        long = "config",
+        env = "CONFIG_PATH",
+        value_name = "PATH",
+        parse(from_os_str)
+    )]
+    pub config_path: Option<PathBuf>,
+
+    /// Path to the audit log, where approvals that bypassed rules are recorded.
+    ///
+    /// Every PR that is force approved, or approved because override labels waived its problems,
+    /// is appended to this file as a line of JSON. Nothing is recorded if it is not set.
+    ///
+    #[structopt(
+        long = "audit-log",
+        env = "AUDIT_LOG_PATH",
+        value_name = "PATH",
+        parse(from_os_str)
+    )]
+    pub audit_log_path: Option<PathBuf>,
+
+    /// Token that admin endpoints must be called with, as "Authorization: Bearer <TOKEN>".
+    ///
+    /// Admin endpoints, like POST /admin/reevaluate/{owner}/{repo} to judge every open PR in a
+    /// repository again, are disabled unless this is set.
+    ///
+    #[structopt(long = "admin-token", env = "ADMIN_TOKEN", value_name = "TOKEN")]
+    pub admin_token: Option<String>,
+
     /// Set the log level of the application.
     ///
     /// You can also set this through the LOG_SPECIFICATION environment variable, but this is a
@@ -97,6 +134,84 @@ pub struct AppOptions {
         default_value = "127.0.0.1:8002",
     )]
     pub bind: SocketAddr,
+
+    /// Run something else than the webhook server.
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    /// Judge a recorded webhook payload without posting any status.
+    ///
+    /// The payload is parsed and judged like a webhook would be, and the gathered intel, the
+    /// judgement, and the status that would have been posted are printed.
+    ///
+    /// Commits are loaded from the Github API unless a file with a recorded response from the
+    /// compare API is given.
+    ///
+    #[structopt(name = "replay")]
+    Replay(ReplayOptions),
+
+    /// Show approvals that bypassed rules, from the audit log.
+    ///
+    /// Records are printed oldest first. Every filter that is given must match.
+    ///
+    #[structopt(name = "audit")]
+    Audit(AuditOptions),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ReplayOptions {
+    /// The name of the event, as sent in the X-Github-Event header.
+    #[structopt(
+        long = "event",
+        value_name = "NAME",
+        default_value = "pull_request",
+    )]
+    pub event_name: String,
+
+    /// Read commits from a recorded compare API response instead of the Github API.
+    #[structopt(long = "commits", value_name = "PATH", parse(from_os_str))]
+    pub commits_path: Option<PathBuf>,
+
+    /// Read changed files from a recorded PR files API response. Only used together with
+    /// `--commits`.
+    #[structopt(long = "files", value_name = "PATH", parse(from_os_str))]
+    pub files_path: Option<PathBuf>,
+
+    /// Use this file as the repository config instead of loading it from the Github API.
+    ///
+    /// The repository config is only loaded from the API if commits are too.
+    #[structopt(long = "repo-config", value_name = "PATH", parse(from_os_str))]
+    pub repo_config_path: Option<PathBuf>,
+
+    /// Path to the recorded webhook payload.
+    #[structopt(value_name = "PAYLOAD", parse(from_os_str))]
+    pub payload_path: PathBuf,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct AuditOptions {
+    /// Only show records for this repository (like "owner/repo").
+    #[structopt(long = "repository", value_name = "OWNER/REPO")]
+    pub repository: Option<String>,
+
+    /// Only show records for this PR number.
+    #[structopt(long = "pr", value_name = "NUMBER")]
+    pub number: Option<u64>,
+
+    /// Only show records where this user applied the labels.
+    #[structopt(long = "actor", value_name = "LOGIN")]
+    pub actor: Option<String>,
+
+    /// Only show records from this time or later, like "2019-02-01T00:00:00Z".
+    #[structopt(long = "since", value_name = "TIME")]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Print the records as JSON, one per line.
+    #[structopt(long = "json")]
+    pub json: bool,
 }
 
 impl AppOptions {
@@ -0,0 +1,1312 @@
+use chrono::Utc;
+use failure::Error;
+
+use audit_log::AuditRecord;
+use config::{Config, ConfigSource, Permission, StatusConfig, REPO_CONFIG_PATH};
+use event::{Label, PullRequest, Repository, User};
+use github_api::{
+    ApiError, Client as GithubClient, CombinedStatus, CommitList, IssueComment, NewStatus,
+    PullRequestFile, State as CommitState, Status,
+};
+use judgement::*;
+use slash_command::{self, SlashCommand};
+use utils::log_error_trace;
+
+/// Everything that was found out about a PR, what was decided about it, and the status that
+/// should be posted because of it.
+#[derive(Debug)]
+pub struct Evaluation<'a> {
+    pub intel: Intel<'a>,
+    pub judgement: Judgement,
+    pub new_status: NewStatus,
+    pub shadow: Option<ShadowEvaluation>,
+}
+
+/// Outcome of the shadow rules. Only posted if the shadow config has its own status context.
+#[derive(Debug)]
+pub struct ShadowEvaluation {
+    pub judgement: Judgement,
+    pub new_status: Option<NewStatus>,
+}
+
+/// Who applied an override label, and whether they may apply it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverrideActor {
+    pub label: String,
+    pub login: Option<String>,
+    pub allowed: bool,
+}
+
+/// A `/prgnome ignore <rule>` command in a comment on the PR, given by someone with write access.
+/// Like override labels, it only counts if the commenter may override rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoreCommand {
+    pub rule: String,
+    pub login: String,
+    pub allowed: bool,
+}
+
+/// Load the config for a PR by merging the config file on the base branch of the PR into the
+/// server config.
+pub fn load_config(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    pr: &PullRequest,
+    server_config: &ConfigSource,
+) -> Result<Config, Error> {
+    let ref_name = pr.base.ref_name.as_ref().unwrap_or(&pr.base.sha);
+    let repo_config =
+        api_client.get_file_contents(auth_token, repo_url, REPO_CONFIG_PATH, ref_name)?;
+
+    let source = match repo_config {
+        Some(contents) => server_config.merged_with(&ConfigSource::parse(&contents)?),
+        None => server_config.clone(),
+    };
+
+    source.config().map_err(Error::from)
+}
+
+/// Load the commits between the base and the head of the PR.
+pub fn load_commits(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    pr: &PullRequest,
+) -> Result<CommitList, ApiError> {
+    api_client.list_commits_in_range(auth_token, repo_url, &pr.base.sha, &pr.head.sha)
+}
+
+/// Load the files changed by the PR, but only if any of the configured rules look at them.
+pub fn load_files(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    pr: &PullRequest,
+    config: &Config,
+) -> Result<Vec<PullRequestFile>, ApiError> {
+    let shadow_needs_files = config
+        .shadow
+        .as_ref()
+        .map_or(false, |shadow| shadow.rules.needs_files());
+
+    if config.rules.needs_files() || shadow_needs_files {
+        api_client.list_pull_request_files(auth_token, repo_url, pr.number)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Find out who applied the override labels on the PR, and if they may. Labels that force approve
+/// the PR are checked too. Nothing is loaded unless the config restricts who may apply override
+/// labels.
+pub fn load_override_actors(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    pr: &PullRequest,
+    config: &Config,
+) -> Result<Vec<OverrideActor>, ApiError> {
+    if !config.overrides.is_restricted() {
+        return Ok(Vec::new());
+    }
+
+    let labels: Vec<&str> = pr
+        .labels
+        .iter()
+        .map(|label| label.name.as_str())
+        .filter(|name| override_rules(config, name).is_some() || force_approves(config, name))
+        .collect();
+    if labels.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let events = api_client.list_issue_events(auth_token, repo_url, pr.number)?;
+    let mut actors = Vec::new();
+
+    for label in labels {
+        // Events are listed oldest first, so the last one is the label that is on the PR now.
+        let login = events
+            .iter()
+            .rev()
+            .filter(|event| event.event == "labeled")
+            .find(|event| {
+                event
+                    .label
+                    .as_ref()
+                    .map_or(false, |l| normalize_label(&l.name) == normalize_label(label))
+            }).and_then(|event| event.actor.as_ref())
+            .map(|actor| actor.login.clone());
+
+        let allowed = match login {
+            Some(ref login) => may_override(api_client, auth_token, repo_url, login, config)?,
+            None => false,
+        };
+
+        actors.push(OverrideActor {
+            label: label.to_string(),
+            login,
+            allowed,
+        });
+    }
+
+    Ok(actors)
+}
+
+/// Find the `/prgnome ignore` commands in the comments on the PR. Commands from people without
+/// write access to the repository are left out, and the others are checked against the override
+/// restrictions in the config. Permissions are only loaded for people who gave commands.
+pub fn load_ignore_commands(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    comments: &[IssueComment],
+    config: &Config,
+) -> Result<Vec<IgnoreCommand>, ApiError> {
+    let mut commands = Vec::new();
+    // Whether each commenter may write, and may override rules.
+    let mut commenters: Vec<(String, bool, bool)> = Vec::new();
+
+    for comment in comments.iter().filter(|comment| comment.user.user_type != "Bot") {
+        for command in slash_command::parse(&comment.body) {
+            let rule = match command {
+                Ok(SlashCommand::Ignore(rule)) => rule,
+                _ => continue,
+            };
+
+            let login = &comment.user.login;
+            let (may_write, allowed) = match commenters.iter().find(|c| c.0 == *login) {
+                Some(&(_, may_write, allowed)) => (may_write, allowed),
+                None => {
+                    let permission = permission_of(api_client, auth_token, repo_url, login)?;
+                    let may_write = permission.map_or(false, |level| level >= Permission::Write);
+                    let allowed = may_write
+                        && (!config.overrides.is_restricted()
+                            || may_override(api_client, auth_token, repo_url, login, config)?);
+                    commenters.push((login.clone(), may_write, allowed));
+                    (may_write, allowed)
+                }
+            };
+
+            if may_write {
+                commands.push(IgnoreCommand {
+                    rule,
+                    login: login.clone(),
+                    allowed,
+                });
+            } else {
+                debug!("Ignoring \"/prgnome ignore {}\" from @{}", rule, login);
+            }
+        }
+    }
+
+    Ok(commands)
+}
+
+/// Permission of a user on the repository, if Github reports a known one.
+pub fn permission_of(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    login: &str,
+) -> Result<Option<Permission>, ApiError> {
+    let permission = api_client.get_collaborator_permission(auth_token, repo_url, login)?;
+    Ok(permission
+        .role_name
+        .as_ref()
+        .and_then(|role| Permission::from_api(role))
+        .or_else(|| Permission::from_api(&permission.permission)))
+}
+
+fn may_override(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    login: &str,
+    config: &Config,
+) -> Result<bool, ApiError> {
+    if let Some(required) = config.overrides.permission {
+        let level = permission_of(api_client, auth_token, repo_url, login)?;
+        if level.map_or(false, |level| level >= required) {
+            return Ok(true);
+        }
+    }
+
+    for team in &config.overrides.teams {
+        let mut parts = team.splitn(2, '/');
+        if let (Some(org), Some(slug)) = (parts.next(), parts.next()) {
+            if api_client.is_team_member(auth_token, org, slug, login)? {
+                return Ok(true);
+            }
+        } else {
+            warn!("Ignoring override team \"{}\". Teams must look like org/team.", team);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Rules waived by an override label, if the label is one.
+fn override_rules<'c>(config: &'c Config, label: &str) -> Option<&'c Vec<String>> {
+    let label = normalize_label(label);
+    config
+        .overrides
+        .labels
+        .iter()
+        .find(|(name, _)| normalize_label(name) == label)
+        .map(|(_, rules)| rules)
+}
+
+/// Whether a label force approves PRs on its own, like the label of the `ignore_label` rule.
+fn force_approves(config: &Config, label: &str) -> bool {
+    let labeled = Intel {
+        label_names: vec![label],
+        ..Default::default()
+    };
+    let shadow_rules = config.shadow.as_ref().map(|shadow| &shadow.rules);
+
+    Some(&config.rules)
+        .into_iter()
+        .chain(shadow_rules)
+        .any(|rules| !labeled.force_approving_labels(rules).is_empty())
+}
+
+/// Label of the override for an ignore command, as shown in statuses and the audit log.
+fn ignore_label(rule: &str) -> String {
+    format!("/prgnome ignore {}", rule)
+}
+
+/// Split the override labels on the PR into the ones that count, and the ones that were applied
+/// by someone who may not. Every ignored rule is also overridden, and force approving labels that
+/// were applied by someone who may not are rejected.
+fn find_overrides(
+    pr: &PullRequest,
+    config: &Config,
+    override_actors: &[OverrideActor],
+    ignore_commands: &[IgnoreCommand],
+) -> (Vec<Override>, Vec<RejectedOverride>) {
+    let mut overrides = Vec::new();
+    let mut rejected = Vec::new();
+
+    for label in &pr.labels {
+        let rules = override_rules(config, &label.name);
+        if rules.is_none() && !force_approves(config, &label.name) {
+            continue;
+        }
+
+        let actor = override_actors
+            .iter()
+            .find(|actor| actor.label == label.name);
+        match actor {
+            _ if !config.overrides.is_restricted() => {}
+            Some(actor) if actor.allowed => {}
+            _ => {
+                rejected.push(RejectedOverride {
+                    label: label.name.clone(),
+                    actor: actor.and_then(|actor| actor.login.clone()),
+                    comment: false,
+                });
+                continue;
+            }
+        }
+
+        if let Some(rules) = rules {
+            overrides.push(Override {
+                label: label.name.clone(),
+                rules: rules.clone(),
+            });
+        }
+    }
+
+    for command in ignore_commands {
+        let label = ignore_label(&command.rule);
+        // A rule is ignored if anyone who may override rules asked for it.
+        let allowed = !config.overrides.is_restricted()
+            || ignore_commands
+                .iter()
+                .any(|other| other.rule == command.rule && other.allowed);
+
+        if !allowed {
+            if rejected.iter().all(|r| r.label != label) {
+                rejected.push(RejectedOverride {
+                    label,
+                    actor: Some(command.login.clone()),
+                    comment: true,
+                });
+            }
+        } else if overrides.iter().all(|o| o.label != label) {
+            overrides.push(Override {
+                label,
+                rules: vec![command.rule.clone()],
+            });
+        }
+    }
+
+    (overrides, rejected)
+}
+
+/// Judge a PR given its commits and changed files. This never talks to the Github API, so it is
+/// safe to use for dry-runs.
+pub fn evaluate<'a>(
+    repository: &Repository,
+    pr: &'a PullRequest,
+    commit_list: CommitList,
+    files: Vec<PullRequestFile>,
+    override_actors: &[OverrideActor],
+    ignore_commands: &[IgnoreCommand],
+    config: &Config,
+) -> Evaluation<'a> {
+    let (overrides, rejected_overrides) =
+        find_overrides(pr, config, override_actors, ignore_commands);
+
+    // Force approving labels that were rejected are left out, so they can't approve the PR.
+    let label_names: Vec<&str> = pr
+        .labels
+        .iter()
+        .map(|label| label.name.as_str())
+        .filter(|name| {
+            !force_approves(config, name)
+                || !rejected_overrides
+                    .iter()
+                    .any(|rejected| !rejected.comment && rejected.label == *name)
+        }).collect();
+
+    let commit_messages = commit_list
+        .commits
+        .iter()
+        .map(|c| c.commit.message.clone())
+        .collect();
+
+    let intel = Intel {
+        title: &pr.title,
+        body: pr.body.as_ref().map_or("", String::as_str),
+        label_names,
+        total_commits: commit_list.total_commits,
+        commit_messages,
+        commits: commit_list.commits,
+        files,
+        overrides,
+        rejected_overrides,
+    };
+
+    let target_url = target_url(&config.status, repository, pr);
+
+    let judgement = intel.validate(&config.rules);
+    let new_status =
+        new_status_from_judgement(&judgement, &config.status.context, target_url.clone());
+
+    let shadow = config.shadow.as_ref().map(|shadow| {
+        let judgement = intel.validate(&shadow.rules);
+        let new_status = shadow
+            .status_context
+            .as_ref()
+            .map(|context| new_status_from_judgement(&judgement, context, target_url.clone()));
+
+        ShadowEvaluation {
+            judgement,
+            new_status,
+        }
+    });
+
+    Evaluation {
+        intel,
+        judgement,
+        new_status,
+        shadow,
+    }
+}
+
+/// Audit record for a PR that was approved even though the rules found problems with it.
+///
+/// `applied_label` is the label and the user that applied it, when the evaluation was triggered by
+/// a label being applied.
+pub fn audit_record(
+    evaluation: &Evaluation,
+    repository: &Repository,
+    pr: &PullRequest,
+    override_actors: &[OverrideActor],
+    ignore_commands: &[IgnoreCommand],
+    applied_label: Option<(&Label, &User)>,
+    config: &Config,
+) -> Option<AuditRecord> {
+    let (labels, waived) = match evaluation.judgement {
+        Judgement::ForceApproved(_) => (
+            evaluation
+                .intel
+                .force_approving_labels(&config.rules)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            evaluation.intel.violations(&config.rules),
+        ),
+        Judgement::Overridden {
+            ref labels,
+            ref waived,
+        } => (labels.clone(), waived.clone()),
+        Judgement::Approved | Judgement::NotApproved { .. } => return None,
+    };
+
+    let actor = labels
+        .iter()
+        .filter_map(|label| match applied_label {
+            Some((applied, sender)) if normalize_label(&applied.name) == normalize_label(label) => {
+                Some(sender.login.clone())
+            }
+            _ => override_actors
+                .iter()
+                .find(|actor| actor.label == *label)
+                .and_then(|actor| actor.login.clone())
+                .or_else(|| {
+                    ignore_commands
+                        .iter()
+                        .find(|command| ignore_label(&command.rule) == *label)
+                        .map(|command| command.login.clone())
+                }),
+        }).next();
+
+    Some(AuditRecord {
+        time: Utc::now(),
+        repository: repository.full_name.clone(),
+        number: pr.number,
+        sha: pr.head.sha.clone(),
+        reason: evaluation.new_status.description.clone().unwrap_or_default(),
+        labels,
+        actor,
+        waived,
+    })
+}
+
+/// Post a status on a commit, unless it already has the same status under the same context.
+pub fn update_status(
+    api_client: &GithubClient,
+    auth_token: &str,
+    repo_url: &str,
+    sha: &str,
+    new_status: NewStatus,
+) -> Result<(), ApiError> {
+    match api_client.get_combined_status(auth_token, repo_url, sha) {
+        Ok(ref current) if has_status(current, &new_status) => {
+            debug!("Status under {} is already up to date", new_status.context);
+            return Ok(());
+        }
+        Ok(_) => {}
+        // Posting the same status again is better than not posting it at all.
+        Err(error) => log_error_trace(&error),
+    }
+
+    api_client
+        .create_status(auth_token, repo_url, sha, new_status)
+        .map(|_| ())
+}
+
+fn has_status(current: &CombinedStatus, new_status: &NewStatus) -> bool {
+    current
+        .statuses
+        .iter()
+        .any(|status| is_same_status(status, new_status))
+}
+
+fn is_same_status(status: &Status, new_status: &NewStatus) -> bool {
+    status.context.as_ref() == Some(&new_status.context)
+        && status.state == new_status.state
+        && status.description == new_status.description
+        && status.target_url == new_status.target_url
+}
+
+/// Status to post while a PR is being judged, so a new commit does not go without a status until
+/// the judgement is done.
+pub fn pending_status(repository: &Repository, pr: &PullRequest, config: &Config) -> NewStatus {
+    NewStatus {
+        state: CommitState::Pending,
+        description: Some(String::from("Checking commits…")),
+        context: config.status.context.clone(),
+        target_url: target_url(&config.status, repository, pr),
+    }
+}
+
+/// Status to post when the config for a repo cannot be loaded.
+pub fn new_status_from_config_error(error: &Error, status_config: &StatusConfig) -> NewStatus {
+    NewStatus {
+        state: CommitState::Error,
+        description: Some(status_description(format!(
+            "Could not load {}: {}",
+            REPO_CONFIG_PATH, error
+        ))),
+        context: status_config.context.clone(),
+        target_url: None,
+    }
+}
+
+fn target_url(
+    status_config: &StatusConfig,
+    repository: &Repository,
+    pr: &PullRequest,
+) -> Option<String> {
+    match (&status_config.target_url, &status_config.public_url) {
+        (Some(template), _) => Some(expand_target_url(template, repository, pr)),
+        (None, Some(public_url)) => Some(format!(
+            "{}/report/{}/{}",
+            public_url.trim_end_matches('/'),
+            repository.full_name,
+            pr.head.sha
+        )),
+        (None, None) => None,
+    }
+}
+
+fn expand_target_url(template: &str, repository: &Repository, pr: &PullRequest) -> String {
+    let mut name_parts = repository.full_name.splitn(2, '/');
+    let owner = name_parts.next().unwrap_or_default();
+    let repo = name_parts.next().unwrap_or(&repository.name);
+
+    template
+        .replace("{owner}", owner)
+        .replace("{repo}", repo)
+        .replace("{number}", &pr.number.to_string())
+        .replace("{sha}", &pr.head.sha)
+}
+
+/// Status for when the files changed by the PR could not be loaded. Rules that look at the files
+/// would blame the PR for missing files, so it is not judged at all.
+pub fn new_status_from_files_error(error: &ApiError, status_config: &StatusConfig) -> NewStatus {
+    NewStatus {
+        state: CommitState::Error,
+        description: Some(status_description(format!(
+            "Could not load the changed files: {}",
+            error
+        ))),
+        context: status_config.context.clone(),
+        target_url: None,
+    }
+}
+
+fn new_status_from_judgement(
+    judgement: &Judgement,
+    context: &str,
+    target_url: Option<String>,
+) -> NewStatus {
+    let (state, description) = match judgement {
+        Judgement::Approved => (CommitState::Success, None),
+        Judgement::ForceApproved(reason) => (CommitState::Success, Some(reason.clone())),
+        Judgement::Overridden { labels, .. } => (
+            CommitState::Success,
+            Some(format!("Approved with overrides: {}", labels.join(", "))),
+        ),
+        Judgement::NotApproved { violations } => {
+            let main_problem = violations
+                .first()
+                .map(|violation| violation.message.as_str())
+                .unwrap_or_default();
+            let message = if violations.len() == 1 {
+                main_problem.to_owned()
+            } else {
+                format!("{} problems. First one: {}", violations.len(), main_problem)
+            };
+            (CommitState::Failure, Some(message))
+        }
+    };
+
+    NewStatus {
+        state,
+        description: description.map(status_description),
+        context: context.into(),
+        target_url,
+    }
+}
+
+/// Github rejects statuses with longer descriptions than this, counted in characters.
+const MAX_DESCRIPTION_LENGTH: usize = 140;
+
+/// Shorten a status description so that Github accepts it.
+fn status_description(description: String) -> String {
+    if description.chars().count() <= MAX_DESCRIPTION_LENGTH {
+        return description;
+    }
+
+    let mut shortened: String = description.chars().take(MAX_DESCRIPTION_LENGTH - 1).collect();
+    shortened.push('…');
+    shortened
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use super::*;
+    use event::{pr_event, read_fixture};
+
+    mod evaluate {
+        use super::*;
+
+        #[test]
+        fn it_judges_recorded_payloads_with_stubbed_commits() {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let commit_list: CommitList =
+                serde_json::from_str(&read_fixture("compare_commits.json")).unwrap();
+
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                commit_list,
+                Vec::new(),
+                &[],
+                &[],
+                &Config::default(),
+            );
+
+            assert_eq!(evaluation.intel.total_commits, 2);
+            assert_eq!(
+                evaluation.intel.commit_messages,
+                vec!["Add example feature", "fixup! Add example feature"],
+            );
+            assert_eq!(
+                evaluation.intel.commits[1].parents[0].sha,
+                evaluation.intel.commits[0].sha,
+            );
+            assert_eq!(
+                evaluation.intel.commits[0]
+                    .commit
+                    .verification
+                    .as_ref()
+                    .map(|v| v.reason.as_str()),
+                Some("unsigned"),
+            );
+            assert_eq!(
+                evaluation.intel.label_names,
+                vec!["Work in progress", "bug", "enhancement"],
+            );
+            assert_eq!(evaluation.new_status.state, CommitState::Failure);
+            assert_eq!(
+                evaluation.new_status.description,
+                Some(String::from(
+                    "2 problems. First one: Rebase away \"fixup! Add example feature\""
+                )),
+            );
+            assert_eq!(evaluation.new_status.context, "mange/prgnome");
+            assert_eq!(evaluation.new_status.target_url, None);
+            assert!(evaluation.shadow.is_none());
+        }
+
+        #[test]
+        fn it_uses_configured_status_context_and_target_url() {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let config = ConfigSource::parse(
+                r#"
+                [status]
+                context = "acme/prgnome-staging"
+                target_url = "https://docs.example.com/prgnome?pr={owner}/{repo}%23{number}&sha={sha}"
+                "#,
+            ).unwrap()
+            .config()
+            .unwrap();
+
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                CommitList::default(),
+                Vec::new(),
+                &[],
+                &[],
+                &config,
+            );
+
+            assert_eq!(evaluation.new_status.context, "acme/prgnome-staging");
+            assert_eq!(
+                evaluation.new_status.target_url,
+                Some(String::from(
+                    "https://docs.example.com/prgnome?pr=example/example-repo%231&sha=f98bf92fcba485635ec3e796c029258e32d18e63"
+                )),
+            );
+        }
+
+        #[test]
+        fn it_judges_with_shadow_rules_when_configured() {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let commit_list: CommitList =
+                serde_json::from_str(&read_fixture("compare_commits.json")).unwrap();
+            let config = ConfigSource::parse(
+                r#"
+                [shadow]
+                status_context = "mange/prgnome (shadow)"
+
+                [shadow.rules.forbidden_intros]
+                enabled = false
+
+                [shadow.rules.forbidden_labels]
+                labels = []
+                "#,
+            ).unwrap()
+            .config()
+            .unwrap();
+
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                commit_list,
+                Vec::new(),
+                &[],
+                &[],
+                &config,
+            );
+            let shadow = evaluation.shadow.unwrap();
+
+            assert_eq!(evaluation.new_status.state, CommitState::Failure);
+            assert_eq!(shadow.judgement, Judgement::Approved);
+
+            let shadow_status = shadow.new_status.unwrap();
+            assert_eq!(shadow_status.state, CommitState::Success);
+            assert_eq!(shadow_status.context, "mange/prgnome (shadow)");
+        }
+
+        #[test]
+        fn it_waives_rules_named_by_override_labels() {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let commit_list: CommitList =
+                serde_json::from_str(&read_fixture("compare_commits.json")).unwrap();
+            let config = ConfigSource::parse(
+                r#"
+                [overrides.labels]
+                "bug" = ["forbidden_intros", "forbidden_labels"]
+                "#,
+            ).unwrap()
+            .config()
+            .unwrap();
+
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                commit_list,
+                Vec::new(),
+                &[],
+                &[],
+                &config,
+            );
+
+            match evaluation.judgement {
+                Judgement::Overridden { labels, waived } => {
+                    assert_eq!(labels, vec!["bug"]);
+                    assert_eq!(waived.len(), 2);
+                }
+                other => panic!("Expected an overridden judgement, got {:#?}", other),
+            }
+            assert_eq!(evaluation.new_status.state, CommitState::Success);
+            assert_eq!(
+                evaluation.new_status.description,
+                Some(String::from("Approved with overrides: bug")),
+            );
+        }
+
+        #[test]
+        fn it_rejects_override_labels_applied_by_others() {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let config = ConfigSource::parse(
+                r#"
+                [rules.forbidden_labels]
+                labels = []
+
+                [overrides]
+                permission = "write"
+
+                [overrides.labels]
+                "bug" = ["forbidden_intros"]
+                "enhancement" = ["max_commits"]
+                "#,
+            ).unwrap()
+            .config()
+            .unwrap();
+            let actors = vec![OverrideActor {
+                label: String::from("bug"),
+                login: Some(String::from("octocat")),
+                allowed: false,
+            }];
+
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                CommitList::default(),
+                Vec::new(),
+                &actors,
+                &[],
+                &config,
+            );
+
+            match evaluation.judgement {
+                Judgement::NotApproved { violations } => {
+                    let messages: Vec<&str> =
+                        violations.iter().map(|v| v.message.as_str()).collect();
+                    assert_eq!(
+                        messages,
+                        vec![
+                            "\"bug\" was applied by @octocat, who may not override rules",
+                            "\"enhancement\" was applied by someone unknown, who may not override rules",
+                        ],
+                    );
+                }
+                other => panic!("Expected a not approved judgement, got {:#?}", other),
+            }
+        }
+
+        fn evaluate_ignore_label(allowed: bool) -> Judgement {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let config = ConfigSource::parse(
+                r#"
+                [rules.ignore_label]
+                label = "bug"
+
+                [overrides]
+                permission = "admin"
+                "#,
+            ).unwrap()
+            .config()
+            .unwrap();
+            let actors = vec![OverrideActor {
+                label: String::from("bug"),
+                login: Some(String::from("octocat")),
+                allowed,
+            }];
+
+            evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                CommitList::default(),
+                Vec::new(),
+                &actors,
+                &[],
+                &config,
+            ).judgement
+        }
+
+        #[test]
+        fn it_force_approves_when_the_ignore_label_was_applied_by_someone_who_may() {
+            assert_eq!(
+                evaluate_ignore_label(true),
+                Judgement::ForceApproved(String::from("Tagged with bug"))
+            );
+        }
+
+        #[test]
+        fn it_rejects_the_ignore_label_when_applied_by_someone_who_may_not() {
+            match evaluate_ignore_label(false) {
+                Judgement::NotApproved { violations } => assert!(violations.iter().any(|v| {
+                    v.message == "\"bug\" was applied by @octocat, who may not override rules"
+                })),
+                other => panic!("Expected a not approved judgement, got {:#?}", other),
+            }
+        }
+    }
+
+    mod find_overrides {
+        use super::*;
+
+        fn ignore(rule: &str, login: &str, allowed: bool) -> IgnoreCommand {
+            IgnoreCommand {
+                rule: rule.into(),
+                login: login.into(),
+                allowed,
+            }
+        }
+
+        #[test]
+        fn it_overrides_ignored_rules_once() {
+            let commands = vec![
+                ignore("max_commits", "octocat", true),
+                ignore("forbidden_labels", "octocat", true),
+                ignore("max_commits", "Mange", true),
+            ];
+
+            let pr_event = pr_event("webhook_pr_labeled.json");
+            let pr = pr_event.pull_request().unwrap();
+
+            let (overrides, rejected) = find_overrides(pr, &Config::default(), &[], &commands);
+
+            assert_eq!(
+                overrides,
+                vec![
+                    Override {
+                        label: String::from("/prgnome ignore max_commits"),
+                        rules: vec![String::from("max_commits")],
+                    },
+                    Override {
+                        label: String::from("/prgnome ignore forbidden_labels"),
+                        rules: vec![String::from("forbidden_labels")],
+                    },
+                ]
+            );
+            assert_eq!(rejected, vec![]);
+        }
+
+        #[test]
+        fn it_rejects_ignore_commands_from_writers_when_overrides_are_restricted() {
+            let config = ConfigSource::parse("[overrides]\npermission = \"admin\"")
+                .unwrap()
+                .config()
+                .unwrap();
+            let commands = vec![
+                ignore("max_commits", "octocat", false),
+                ignore("forbidden_labels", "octocat", false),
+                ignore("forbidden_labels", "Mange", true),
+            ];
+
+            let pr_event = pr_event("webhook_pr_labeled.json");
+            let pr = pr_event.pull_request().unwrap();
+
+            let (overrides, rejected) = find_overrides(pr, &config, &[], &commands);
+
+            assert_eq!(
+                overrides,
+                vec![Override {
+                    label: String::from("/prgnome ignore forbidden_labels"),
+                    rules: vec![String::from("forbidden_labels")],
+                }]
+            );
+            assert_eq!(
+                rejected,
+                vec![RejectedOverride {
+                    label: String::from("/prgnome ignore max_commits"),
+                    actor: Some(String::from("octocat")),
+                    comment: true,
+                }]
+            );
+        }
+    }
+
+    mod audit_record {
+        use super::*;
+
+        fn audit(config: &str) -> Option<AuditRecord> {
+            let pr_event = pr_event("webhook_pr_labeled.json");
+            let config = ConfigSource::parse(config).unwrap().config().unwrap();
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                CommitList::default(),
+                Vec::new(),
+                &[],
+                &[],
+                &config,
+            );
+
+            audit_record(
+                &evaluation,
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                &[],
+                &[],
+                pr_event.applied_label(),
+                &config,
+            )
+        }
+
+        #[test]
+        fn it_records_who_applied_override_labels() {
+            let record = audit("[overrides.labels]\n\"enhancement\" = [\"forbidden_labels\"]")
+                .unwrap();
+
+            assert_eq!(record.repository, "example/example-repo");
+            assert_eq!(record.number, 1);
+            assert_eq!(record.sha, "071fc7d8b57bc286488fe1a76a2aa566eca941d6");
+            assert_eq!(record.reason, "Approved with overrides: enhancement");
+            assert_eq!(record.labels, vec!["enhancement"]);
+            assert_eq!(record.actor, Some(String::from("Mange")));
+            assert_eq!(record.waived.len(), 1);
+            assert_eq!(record.waived[0].rule, "forbidden_labels");
+        }
+
+        #[test]
+        fn it_records_who_gave_ignore_commands() {
+            let pr_event = pr_event("webhook_pr_labeled.json");
+            let config = Config::default();
+            let commands = vec![IgnoreCommand {
+                rule: String::from("forbidden_labels"),
+                login: String::from("octocat"),
+                allowed: true,
+            }];
+            let evaluation = evaluate(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                CommitList::default(),
+                Vec::new(),
+                &[],
+                &commands,
+                &config,
+            );
+
+            let record = audit_record(
+                &evaluation,
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                &[],
+                &commands,
+                None,
+                &config,
+            ).unwrap();
+
+            assert_eq!(
+                record.reason,
+                "Approved with overrides: /prgnome ignore forbidden_labels"
+            );
+            assert_eq!(record.labels, vec!["/prgnome ignore forbidden_labels"]);
+            assert_eq!(record.actor, Some(String::from("octocat")));
+        }
+
+        #[test]
+        fn it_records_force_approvals() {
+            let record = audit("[rules.ignore_label]\nlabel = \"Work in progress\"").unwrap();
+
+            assert_eq!(record.reason, "Tagged with Work in progress");
+            assert_eq!(record.labels, vec!["Work in progress"]);
+            assert_eq!(record.actor, None);
+            assert_eq!(record.waived[0].rule, "forbidden_labels");
+        }
+
+        #[test]
+        fn it_does_not_record_other_judgements() {
+            assert_eq!(audit(""), None);
+            assert_eq!(audit("[rules.forbidden_labels]\nlabels = []"), None);
+        }
+    }
+
+    mod target_url {
+        use super::*;
+
+        #[test]
+        fn it_links_to_reports_on_the_public_url() {
+            let pr_event = pr_event("webhook_pr_labeled.json");
+            let status_config = StatusConfig {
+                public_url: Some(String::from("https://prgnome.example.com/")),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                target_url(
+                    &status_config,
+                    pr_event.repository().unwrap(),
+                    pr_event.pull_request().unwrap(),
+                ),
+                Some(format!(
+                    "https://prgnome.example.com/report/example/example-repo/{}",
+                    pr_event.pull_request().unwrap().head.sha,
+                )),
+            );
+        }
+
+        #[test]
+        fn it_prefers_the_configured_target_url() {
+            let pr_event = pr_event("webhook_pr_labeled.json");
+            let status_config = StatusConfig {
+                target_url: Some(String::from("https://docs.example.com/{repo}")),
+                public_url: Some(String::from("https://prgnome.example.com")),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                target_url(
+                    &status_config,
+                    pr_event.repository().unwrap(),
+                    pr_event.pull_request().unwrap(),
+                ),
+                Some(String::from("https://docs.example.com/example-repo")),
+            );
+        }
+
+        #[test]
+        fn it_has_no_link_by_default() {
+            let pr_event = pr_event("webhook_pr_labeled.json");
+
+            assert_eq!(
+                target_url(
+                    &StatusConfig::default(),
+                    pr_event.repository().unwrap(),
+                    pr_event.pull_request().unwrap(),
+                ),
+                None,
+            );
+        }
+    }
+
+    mod pending_status {
+        use super::*;
+
+        #[test]
+        fn it_uses_the_configured_status_context() {
+            let pr_event = pr_event("webhook_pr_synchronized.json");
+            let config = ConfigSource::parse("[status]\ncontext = \"acme/prgnome\"")
+                .unwrap()
+                .config()
+                .unwrap();
+
+            let status = pending_status(
+                pr_event.repository().unwrap(),
+                pr_event.pull_request().unwrap(),
+                &config,
+            );
+
+            assert_eq!(status.state, CommitState::Pending);
+            assert_eq!(status.context, "acme/prgnome");
+            assert_eq!(status.description, Some(String::from("Checking commits…")));
+        }
+    }
+
+    mod has_status {
+        use super::*;
+
+        fn new_status() -> NewStatus {
+            NewStatus {
+                state: CommitState::Failure,
+                context: String::from("mange/prgnome"),
+                target_url: None,
+                description: Some(String::from("Rebase away \"fixup! Add feature\"")),
+            }
+        }
+
+        fn current(statuses: &[(CommitState, &str, &str)]) -> CombinedStatus {
+            CombinedStatus {
+                state: CommitState::Failure,
+                statuses: statuses
+                    .iter()
+                    .map(|&(state, context, description)| Status {
+                        state,
+                        target_url: None,
+                        description: Some(description.to_owned()),
+                        context: Some(context.to_owned()),
+                    }).collect(),
+            }
+        }
+
+        #[test]
+        fn it_finds_the_same_status_under_the_context() {
+            let current = current(&[
+                (CommitState::Success, "ci/build", "All tests passed"),
+                (
+                    CommitState::Failure,
+                    "mange/prgnome",
+                    "Rebase away \"fixup! Add feature\"",
+                ),
+            ]);
+            assert!(has_status(&current, &new_status()));
+        }
+
+        #[test]
+        fn it_notices_changed_statuses() {
+            let pending = current(&[(CommitState::Pending, "mange/prgnome", "Checking commits…")]);
+            assert!(!has_status(&pending, &new_status()));
+
+            let other_description = current(&[(
+                CommitState::Failure,
+                "mange/prgnome",
+                "2 problems. First one: Remove the \"wip\" label",
+            )]);
+            assert!(!has_status(&other_description, &new_status()));
+
+            let other_context = current(&[(
+                CommitState::Failure,
+                "acme/prgnome",
+                "Rebase away \"fixup! Add feature\"",
+            )]);
+            assert!(!has_status(&other_context, &new_status()));
+
+            assert!(!has_status(&current(&[]), &new_status()));
+        }
+    }
+
+    mod new_status_from_config_error {
+        use super::*;
+
+        #[test]
+        fn it_shortens_long_errors() {
+            let error = format_err!("expected a table key, found {}", "å".repeat(200));
+            let status = new_status_from_config_error(&error, &StatusConfig::default());
+            let description = status.description.unwrap();
+
+            assert_eq!(description.chars().count(), 140);
+            assert!(description.starts_with("Could not load .github/prgnome.toml: expected a"));
+            assert!(description.ends_with("åå…"));
+        }
+
+        #[test]
+        fn it_keeps_short_errors() {
+            let error = format_err!("invalid type");
+            let status = new_status_from_config_error(&error, &StatusConfig::default());
+
+            assert_eq!(
+                status.description,
+                Some(String::from("Could not load .github/prgnome.toml: invalid type"))
+            );
+        }
+    }
+
+    mod new_status_from_judgement {
+        use super::*;
+
+        fn not_approved(messages: &[&str]) -> Judgement {
+            Judgement::NotApproved {
+                violations: messages
+                    .iter()
+                    .map(|message| Violation {
+                        rule: String::from("forbidden_intros"),
+                        message: message.to_string(),
+                        hint: String::new(),
+                    }).collect(),
+            }
+        }
+
+        #[test]
+        fn it_returns_success_on_approved_judgement() {
+            let judgement = Judgement::Approved;
+            let new_state = new_status_from_judgement(&judgement, "prgnome", None);
+
+            assert_eq!(new_state.state, CommitState::Success);
+            assert_eq!(new_state.description, None);
+        }
+
+        #[test]
+        fn it_shortens_long_problems() {
+            let long_message = format!("Rebase away \"fixup! {}\"", "x".repeat(200));
+            let judgement = not_approved(&[&long_message, "Remove the \"wip\" label"]);
+            let new_state = new_status_from_judgement(&judgement, "prgnome", None);
+            let description = new_state.description.unwrap();
+
+            assert_eq!(description.chars().count(), 140);
+            assert!(description.starts_with("2 problems. First one: Rebase away \"fixup! xxx"));
+            assert!(description.ends_with("xx…"));
+        }
+
+        #[test]
+        fn it_returns_failure_on_not_approved_judgement_with_single_problem() {
+            let judgement = not_approved(&["Not cool enough"]);
+            let new_state = new_status_from_judgement(&judgement, "prgnome", None);
+
+            assert_eq!(new_state.state, CommitState::Failure);
+            assert_eq!(new_state.description, Some(String::from("Not cool enough")));
+        }
+
+        #[test]
+        fn it_returns_failure_on_not_approved_judgement_with_multiple_problems() {
+            let judgement = not_approved(&[
+                "Not cool enough",
+                "Too warm",
+                "Not enough bling",
+                "Wrong hat",
+            ]);
+            let new_state = new_status_from_judgement(&judgement, "prgnome", None);
+
+            assert_eq!(new_state.state, CommitState::Failure);
+            assert_eq!(
+                new_state.description,
+                Some(String::from("4 problems. First one: Not cool enough")),
+            );
+        }
+
+        #[test]
+        fn it_returns_success_on_force_approved_judgement() {
+            let judgement = Judgement::ForceApproved(String::from("Tagged with something cool"));
+            let new_state = new_status_from_judgement(&judgement, "prgnome", None);
+
+            assert_eq!(new_state.state, CommitState::Success);
+            assert_eq!(
+                new_state.description,
+                Some(String::from("Tagged with something cool")),
+            );
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+/// Another run of judging the open PRs in a repository, requested while it was already being done.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rerun {
+    /// Only judge PRs against this branch. `None` judges every open PR.
+    pub base_branch: Option<String>,
+}
+
+/// Keeps track of the repositories whose open PRs are being judged again, so only one thread
+/// judges each repository at a time. Requests for a repository that is already being judged are
+/// coalesced into a single run after the current one.
+#[derive(Debug, Default)]
+pub struct ReevaluationQueue {
+    running: HashMap<String, Option<Rerun>>,
+}
+
+impl ReevaluationQueue {
+    /// Returns `true` if the caller should judge the repository now. Otherwise another run is
+    /// queued for whoever is judging it already.
+    pub fn start(&mut self, repository: &str, base_branch: Option<String>) -> bool {
+        match self.running.get_mut(repository) {
+            Some(rerun) => {
+                // Runs for different branches are merged into one for every branch.
+                let base_branch = match *rerun {
+                    Some(ref queued) if queued.base_branch != base_branch => None,
+                    _ => base_branch,
+                };
+                *rerun = Some(Rerun { base_branch });
+                false
+            }
+            None => {
+                self.running.insert(repository.to_owned(), None);
+                true
+            }
+        }
+    }
+
+    /// Called when a run is done. Returns the run that was queued meanwhile, which the caller
+    /// should do next, or `None` if the repository is no longer being judged.
+    pub fn finish(&mut self, repository: &str) -> Option<Rerun> {
+        let rerun = self.running.get_mut(repository).and_then(Option::take);
+        if rerun.is_none() {
+            self.running.remove(repository);
+        }
+        rerun
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rerun(base_branch: Option<&str>) -> Option<Rerun> {
+        Some(Rerun {
+            base_branch: base_branch.map(String::from),
+        })
+    }
+
+    #[test]
+    fn it_runs_each_repository_once_at_a_time() {
+        let mut queue = ReevaluationQueue::default();
+
+        assert!(queue.start("example/repo", None));
+        assert!(queue.start("example/other", None));
+        assert!(!queue.start("example/repo", None));
+
+        assert_eq!(queue.finish("example/other"), None);
+        assert!(queue.start("example/other", None));
+    }
+
+    #[test]
+    fn it_coalesces_requests_into_one_more_run() {
+        let mut queue = ReevaluationQueue::default();
+        queue.start("example/repo", None);
+
+        assert!(!queue.start("example/repo", Some(String::from("master"))));
+        assert!(!queue.start("example/repo", Some(String::from("master"))));
+
+        assert_eq!(queue.finish("example/repo"), rerun(Some("master")));
+        assert!(!queue.start("example/repo", None));
+        assert_eq!(queue.finish("example/repo"), rerun(None));
+        assert_eq!(queue.finish("example/repo"), None);
+        assert!(queue.start("example/repo", None));
+    }
+
+    #[test]
+    fn it_judges_every_branch_when_requests_for_different_branches_are_coalesced() {
+        let mut queue = ReevaluationQueue::default();
+        queue.start("example/repo", Some(String::from("master")));
+
+        queue.start("example/repo", Some(String::from("master")));
+        queue.start("example/repo", Some(String::from("develop")));
+        queue.start("example/repo", Some(String::from("develop")));
+
+        assert_eq!(queue.finish("example/repo"), rerun(None));
+    }
+}
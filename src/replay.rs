@@ -0,0 +1,160 @@
+extern crate serde_json;
+
+use failure::{Error, ResultExt};
+use std::fs;
+
+use comment;
+use config::ConfigSource;
+use event::Event;
+use github_api::{Client as GithubClient, CommitList, PullRequestFile};
+use options::ReplayOptions;
+use pipeline;
+
+/// Judge a recorded webhook payload and print the outcome instead of posting it.
+///
+/// The API client is only created if commits need to be loaded from the Github API.
+pub fn run<F>(
+    options: &ReplayOptions,
+    server_config: &ConfigSource,
+    api_client: F,
+) -> Result<(), Error>
+where
+    F: FnOnce() -> Result<GithubClient, Error>,
+{
+    let payload = fs::read_to_string(&options.payload_path).context("Could not read payload")?;
+    let pr_event = match Event::parse_json(&options.event_name, &payload)? {
+        Event::PullRequest(pr_event) => pr_event,
+        Event::Unknown { name, .. } => bail!("prgnome does not judge {} events", name),
+        _ => bail!("prgnome only judges pull_request events"),
+    };
+
+    let (repo_url, repository, pr, installation) = match (
+        pr_event.repo_url(),
+        pr_event.repository(),
+        pr_event.pull_request(),
+        pr_event.installation(),
+    ) {
+        (Some(repo_url), Some(repository), Some(pr), Some(installation)) => {
+            (repo_url, repository, pr, installation)
+        }
+        _ => bail!("prgnome does not judge this kind of pull_request event"),
+    };
+
+    let repo_config = match options.repo_config_path {
+        Some(ref path) => Some(ConfigSource::read(path).context("Could not load repo config")?),
+        None => None,
+    };
+
+    let (config, commit_list, files, override_actors, ignore_commands) =
+        match options.commits_path {
+            Some(ref path) => {
+                let json = fs::read_to_string(path).context("Could not read commits")?;
+                let commit_list: CommitList =
+                    serde_json::from_str(&json).context("Could not parse commits")?;
+                let files: Vec<PullRequestFile> = match options.files_path {
+                    Some(ref path) => {
+                        let json = fs::read_to_string(path).context("Could not read files")?;
+                        serde_json::from_str(&json).context("Could not parse files")?
+                    }
+                    None => Vec::new(),
+                };
+                let source = match repo_config {
+                    Some(ref repo_config) => server_config.merged_with(repo_config),
+                    None => server_config.clone(),
+                };
+                let config = source.config()?;
+                if config.overrides.is_restricted() {
+                    println!(
+                        "Override labels cannot be verified without the Github API. Rejecting them."
+                    );
+                }
+                (config, commit_list, files, Vec::new(), Vec::new())
+            }
+            None => {
+                let api_client = api_client()?;
+                let auth_token = api_client
+                    .generate_auth_token(installation.id)
+                    .context("Could not load auth token")?;
+                let config = match repo_config {
+                    Some(ref repo_config) => server_config.merged_with(repo_config).config()?,
+                    None => pipeline::load_config(
+                        &api_client,
+                        &auth_token,
+                        repo_url,
+                        pr,
+                        server_config,
+                    ).context("Could not load config")?,
+                };
+                let commit_list = pipeline::load_commits(&api_client, &auth_token, repo_url, pr)
+                    .context("Could not load commits")?;
+                let files = pipeline::load_files(&api_client, &auth_token, repo_url, pr, &config)
+                    .context("Could not load files")?;
+                let override_actors =
+                    pipeline::load_override_actors(&api_client, &auth_token, repo_url, pr, &config)
+                        .context("Could not check override labels")?;
+                let comments = api_client
+                    .list_issue_comments(&auth_token, repo_url, pr.number)
+                    .context("Could not load comments")?;
+                let ignore_commands = pipeline::load_ignore_commands(
+                    &api_client,
+                    &auth_token,
+                    repo_url,
+                    &comments,
+                    &config,
+                ).context("Could not load ignore commands")?;
+                (config, commit_list, files, override_actors, ignore_commands)
+            }
+        };
+
+    let evaluation = pipeline::evaluate(
+        repository,
+        pr,
+        commit_list,
+        files,
+        &override_actors,
+        &ignore_commands,
+        &config,
+    );
+
+    println!("Rules: {}", config.rules.rule_names().join(", "));
+    println!("Intel: {:#?}", evaluation.intel);
+    println!("Judgement: {:#?}", evaluation.judgement);
+    println!(
+        "Status (not posted): {}",
+        serde_json::to_string_pretty(&evaluation.new_status)?
+    );
+
+    if let Some(record) = pipeline::audit_record(
+        &evaluation,
+        repository,
+        pr,
+        &override_actors,
+        &ignore_commands,
+        pr_event.applied_label(),
+        &config,
+    ) {
+        println!(
+            "Audit record (not written): {}",
+            serde_json::to_string_pretty(&record)?
+        );
+    }
+
+    if config.comments.enabled {
+        match comment::render(&evaluation.judgement, &config.status.context, pr) {
+            Some(body) => println!("Comment (not posted):\n{}", body),
+            None => println!("Comment: {:?} (not done)", config.comments.on_success),
+        }
+    }
+
+    if let Some(shadow) = evaluation.shadow {
+        println!("Shadow judgement: {:#?}", shadow.judgement);
+        if let Some(new_status) = shadow.new_status {
+            println!(
+                "Shadow status (not posted): {}",
+                serde_json::to_string_pretty(&new_status)?
+            );
+        }
+    }
+
+    Ok(())
+}
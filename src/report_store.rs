@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use judgement::Judgement;
+
+const MAX_REPORTS: usize = 10_000;
+
+/// The full judgement of a commit in a PR, kept so it can be looked at after the status has been
+/// posted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub repository: String,
+    pub number: u64,
+    pub sha: String,
+    pub judgement: Judgement,
+}
+
+/// Keeps the latest report for each commit. The oldest reports are forgotten when the store is
+/// full.
+#[derive(Debug, Default)]
+pub struct ReportStore {
+    reports: HashMap<(String, String), (Instant, Report)>,
+}
+
+impl ReportStore {
+    pub fn add_report(&mut self, report: Report) {
+        let key = (report.repository.clone(), report.sha.clone());
+        // Replacing the report of a commit doesn't make the store any fuller.
+        if self.reports.len() >= MAX_REPORTS && !self.reports.contains_key(&key) {
+            self.evict_oldest();
+        }
+
+        self.reports.insert(key, (Instant::now(), report));
+    }
+
+    pub fn get_report(&self, repository: &str, sha: &str) -> Option<Report> {
+        self.reports
+            .get(&(repository.to_owned(), sha.to_owned()))
+            .map(|(_, report)| report.clone())
+    }
+
+    /// Forget every report for a repository, like when the app is uninstalled from it.
+    pub fn remove_repository(&mut self, repository: &str) {
+        self.reports.retain(|(report_repository, _), _| report_repository != repository);
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .reports
+            .iter()
+            .min_by_key(|(_, (time, _))| *time)
+            .map(|(key, _)| key.clone());
+
+        if let Some(key) = oldest {
+            debug!("Report store is full. Forgetting report for {:?}", key);
+            self.reports.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn report(repository: &str, sha: &str) -> Report {
+        Report {
+            repository: repository.into(),
+            number: 1,
+            sha: sha.into(),
+            judgement: Judgement::Approved,
+        }
+    }
+
+    /// A full store, where the report for commit "0" is the oldest one.
+    fn full_store() -> ReportStore {
+        let mut store = ReportStore::default();
+        let start = Instant::now();
+        for i in 0..MAX_REPORTS {
+            store.reports.insert(
+                (String::from("example/repo"), i.to_string()),
+                (
+                    start + Duration::from_millis(i as u64),
+                    report("example/repo", &i.to_string()),
+                ),
+            );
+        }
+        store
+    }
+
+    #[test]
+    fn it_stores_and_retrieves_reports() {
+        let mut store = ReportStore::default();
+        store.add_report(report("example/repo", "abc"));
+
+        assert_eq!(
+            store.get_report("example/repo", "abc"),
+            Some(report("example/repo", "abc"))
+        );
+        assert_eq!(store.get_report("example/repo", "def"), None);
+        assert_eq!(store.get_report("example/other", "abc"), None);
+    }
+
+    #[test]
+    fn it_replaces_older_reports_for_the_same_commit() {
+        let mut store = ReportStore::default();
+        store.add_report(report("example/repo", "abc"));
+        store.add_report(Report {
+            judgement: Judgement::ForceApproved(String::from("Tagged with prgnome ignore")),
+            ..report("example/repo", "abc")
+        });
+
+        assert_eq!(
+            store.get_report("example/repo", "abc").unwrap().judgement,
+            Judgement::ForceApproved(String::from("Tagged with prgnome ignore"))
+        );
+    }
+
+    #[test]
+    fn it_removes_reports_for_a_repository() {
+        let mut store = ReportStore::default();
+        store.add_report(report("example/repo", "abc"));
+        store.add_report(report("example/repo", "def"));
+        store.add_report(report("example/other", "abc"));
+
+        store.remove_repository("example/repo");
+
+        assert_eq!(store.get_report("example/repo", "abc"), None);
+        assert_eq!(store.get_report("example/repo", "def"), None);
+        assert!(store.get_report("example/other", "abc").is_some());
+    }
+
+    #[test]
+    fn it_forgets_the_oldest_report_when_full() {
+        let mut store = full_store();
+
+        store.add_report(report("example/repo", "new"));
+
+        assert_eq!(store.reports.len(), MAX_REPORTS);
+        assert_eq!(store.get_report("example/repo", "0"), None);
+        assert!(store.get_report("example/repo", "1").is_some());
+        assert!(store.get_report("example/repo", "new").is_some());
+    }
+
+    #[test]
+    fn it_keeps_other_reports_when_replacing_one_in_a_full_store() {
+        let mut store = full_store();
+
+        store.add_report(report("example/repo", "5"));
+
+        assert_eq!(store.reports.len(), MAX_REPORTS);
+        assert!(store.get_report("example/repo", "0").is_some());
+    }
+}
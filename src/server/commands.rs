@@ -0,0 +1,79 @@
+use super::judge::judge_pull_request;
+use super::ServerState;
+use comment;
+use config::Permission;
+use event::{Comment, Issue, Repository};
+use github_api::ApiError;
+use pipeline;
+use slash_command::{self, SlashCommand};
+
+/// Run the slash commands in a new comment on a PR, like `/prgnome recheck`. Only people with
+/// write access to the repository may give commands.
+///
+/// The comment gets a "+1" reaction when the commands are run, and a reply if they are not or if
+/// there is something to explain.
+pub fn run_commands(
+    state: &ServerState,
+    auth_token: &str,
+    repository: &Repository,
+    issue: &Issue,
+    comment: &Comment,
+) -> Result<(), ApiError> {
+    let commands = slash_command::parse(&comment.body);
+    if commands.is_empty() || issue.pull_request.is_none() || comment.user.login.ends_with("[bot]")
+    {
+        return Ok(());
+    }
+
+    let api_client = &state.api_client;
+    let repo_url = &repository.url;
+    let login = &comment.user.login;
+    let reply =
+        |body: &str| api_client.create_issue_comment(auth_token, repo_url, issue.number, body);
+
+    let permission = pipeline::permission_of(api_client, auth_token, repo_url, login)?;
+    if !permission.map_or(false, |level| level >= Permission::Write) {
+        info!("Ignoring commands from @{}, who has no write access", login);
+        api_client.create_comment_reaction(auth_token, repo_url, comment.id, "-1")?;
+        reply(&format!(
+            "@{} Only people with write access to this repository can give commands to prgnome.",
+            login
+        ))?;
+        return Ok(());
+    }
+
+    let errors: Vec<String> = commands
+        .iter()
+        .filter_map(|command| command.as_ref().err())
+        .map(|error| format!("* {}", error))
+        .collect();
+    if !errors.is_empty() {
+        api_client.create_comment_reaction(auth_token, repo_url, comment.id, "confused")?;
+        reply(&format!(
+            "@{} prgnome did not understand your comment, so nothing was done:\n\n{}\n",
+            login,
+            errors.join("\n")
+        ))?;
+        return Ok(());
+    }
+
+    info!(
+        "Running commands from @{} on PR #{} in {}",
+        login, issue.number, repository.full_name
+    );
+    api_client.create_comment_reaction(auth_token, repo_url, comment.id, "+1")?;
+
+    // Ignore commands are read from the comments on the PR, so judging it again is enough to
+    // apply them.
+    let pr = api_client.get_pull_request(auth_token, repo_url, issue.number)?;
+    let judgement = judge_pull_request(state, auth_token, repo_url, repository, &pr, None, false);
+
+    if commands.contains(&Ok(SlashCommand::Explain)) {
+        match judgement {
+            Some(judgement) => reply(&comment::explain(&judgement))?,
+            None => reply("prgnome could not load the config of this PR. See its status for why.")?,
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use super::ServerState;
+use comment;
+use event::{Label, PullRequest, Repository, User};
+use github_api::NewStatus;
+use judgement::Judgement;
+use pipeline;
+use report_store::Report;
+use utils::{log_error_trace, log_error_trace_if_err};
+
+/// Judge a PR and post the outcome: the status, the explanation comment, and the shadow status.
+/// Problems with the Github API are logged. Returns the judgement, or `None` if the config or the
+/// changed files could not be loaded.
+///
+/// `applied_label` is the label and the user that applied it, when a label was just applied.
+/// A pending status is posted first when the PR has a new head commit. It is posted before the
+/// config is loaded, so it uses the context of the server config.
+pub fn judge_pull_request(
+    state: &ServerState,
+    auth_token: &str,
+    repo_url: &str,
+    repository: &Repository,
+    pr: &PullRequest,
+    applied_label: Option<(&Label, &User)>,
+    new_head: bool,
+) -> Option<Judgement> {
+    let pending_context = if new_head {
+        debug!("Setting pending status on new head");
+        let server_config = state.server_config();
+        log_error_trace_if_err(&state.api_client.create_status(
+            auth_token,
+            repo_url,
+            &pr.head.sha,
+            pipeline::pending_status(repository, pr, server_config),
+        ));
+        Some(server_config.status.context.as_str())
+    } else {
+        None
+    };
+
+    debug!("Loading config");
+    let config =
+        match pipeline::load_config(&state.api_client, auth_token, repo_url, pr, state.config()) {
+            Ok(config) => config,
+            Err(error) => {
+                log_error_trace(error.as_fail());
+                log_error_trace_if_err(&pipeline::update_status(
+                    &state.api_client,
+                    auth_token,
+                    repo_url,
+                    &pr.head.sha,
+                    pipeline::new_status_from_config_error(&error, &state.server_config().status),
+                ));
+                return None;
+            }
+        };
+
+    // The config of the repository can change the context. The pending status must not be left
+    // behind under the old context then.
+    let stale_context = pending_context.filter(|context| *context != config.status.context);
+
+    debug!("Loading commits in PR range");
+    let commit_list = pipeline::load_commits(&state.api_client, auth_token, repo_url, pr)
+        .unwrap_or_else(|err| {
+            log_error_trace(&err);
+            Default::default()
+        });
+
+    debug!("Loading changed files");
+    let files = match pipeline::load_files(&state.api_client, auth_token, repo_url, pr, &config) {
+        Ok(files) => files,
+        Err(error) => {
+            log_error_trace(&error);
+            post_status(
+                state,
+                auth_token,
+                repo_url,
+                pr,
+                pipeline::new_status_from_files_error(&error, &config.status),
+                stale_context,
+            );
+            return None;
+        }
+    };
+
+    debug!("Checking who applied override labels");
+    let override_actors =
+        pipeline::load_override_actors(&state.api_client, auth_token, repo_url, pr, &config)
+            .unwrap_or_else(|err| {
+                log_error_trace(&err);
+                Default::default()
+            });
+
+    // Listed once, for both the ignore commands and the explanation comment.
+    debug!("Loading comments");
+    let comments = state
+        .api_client
+        .list_issue_comments(auth_token, repo_url, pr.number);
+    if let Err(ref err) = comments {
+        log_error_trace(err);
+    }
+
+    let ignore_commands = match comments {
+        Ok(ref comments) => pipeline::load_ignore_commands(
+            &state.api_client,
+            auth_token,
+            repo_url,
+            comments,
+            &config,
+        ).unwrap_or_else(|err| {
+            log_error_trace(&err);
+            Default::default()
+        }),
+        Err(_) => Vec::new(),
+    };
+
+    let evaluation = pipeline::evaluate(
+        repository,
+        pr,
+        commit_list,
+        files,
+        &override_actors,
+        &ignore_commands,
+        &config,
+    );
+    debug!("Judgement: {:?}", evaluation.judgement);
+
+    // Only record approvals once per commit, and not again for unrelated events.
+    let previous = state.get_report(&repository.full_name, &pr.head.sha);
+    if previous.map_or(true, |report| report.judgement != evaluation.judgement) {
+        if let Some(record) = pipeline::audit_record(
+            &evaluation,
+            repository,
+            pr,
+            &override_actors,
+            &ignore_commands,
+            applied_label,
+            &config,
+        ) {
+            state.audit(&record);
+        }
+    }
+
+    state.add_report(Report {
+        repository: repository.full_name.clone(),
+        number: pr.number,
+        sha: pr.head.sha.clone(),
+        judgement: evaluation.judgement.clone(),
+    });
+
+    info!("Setting new status to: {:#?}", evaluation.new_status);
+    post_status(
+        state,
+        auth_token,
+        repo_url,
+        pr,
+        evaluation.new_status,
+        stale_context,
+    );
+
+    if config.comments.enabled {
+        // Without the comments, the existing explanation can't be found. Leave it for next time.
+        if let Ok(ref comments) = comments {
+            debug!("Updating explanation comment");
+            log_error_trace_if_err(&comment::sync(
+                &state.api_client,
+                auth_token,
+                repo_url,
+                pr,
+                comments,
+                &config,
+                &evaluation.judgement,
+            ));
+        }
+    }
+
+    if let Some(shadow) = evaluation.shadow {
+        if shadow.judgement != evaluation.judgement {
+            info!(
+                "Shadow rules disagree on PR #{} in {}. Active: {:?}, shadow: {:?}",
+                pr.number, repo_url, evaluation.judgement, shadow.judgement
+            );
+        }
+
+        if let Some(new_status) = shadow.new_status {
+            debug!("Setting new shadow status to: {:#?}", new_status);
+            log_error_trace_if_err(&pipeline::update_status(
+                &state.api_client,
+                auth_token,
+                repo_url,
+                &pr.head.sha,
+                new_status,
+            ));
+        }
+    }
+
+    Some(evaluation.judgement)
+}
+
+/// Post a status on the head of the PR. `stale_context` is another context with a pending status
+/// on the commit, which gets the same status so that it doesn't stay pending.
+fn post_status(
+    state: &ServerState,
+    auth_token: &str,
+    repo_url: &str,
+    pr: &PullRequest,
+    new_status: NewStatus,
+    stale_context: Option<&str>,
+) {
+    if let Some(context) = stale_context {
+        log_error_trace_if_err(&pipeline::update_status(
+            &state.api_client,
+            auth_token,
+            repo_url,
+            &pr.head.sha,
+            NewStatus {
+                state: new_status.state,
+                context: context.into(),
+                target_url: new_status.target_url.clone(),
+                description: new_status.description.clone(),
+            },
+        ));
+    }
+
+    log_error_trace_if_err(&pipeline::update_status(
+        &state.api_client,
+        auth_token,
+        repo_url,
+        &pr.head.sha,
+        new_status,
+    ));
+}
@@ -1,12 +1,20 @@
+mod commands;
 mod index;
+mod judge;
+mod reevaluate;
+mod report;
 mod webhook;
 
 use actix_web::{http, HttpResponse, Result};
+use audit_log::{AuditLog, AuditRecord};
+use config::{Config, ConfigSource};
 use event::EventError;
 use github_api::{ApiError, Client as GithubClient};
-use std::sync::RwLock;
+use reevaluation_queue::{ReevaluationQueue, Rerun};
+use report_store::{Report, ReportStore};
+use std::sync::{Mutex, RwLock};
 use token_store::TokenStore;
-use utils::log_error_trace;
+use utils::{log_error_trace, log_error_trace_if_err};
 
 mod prelude {
     pub use super::ServerState;
@@ -15,22 +23,50 @@ mod prelude {
 }
 
 pub use self::index::handle_index;
+pub use self::reevaluate::handle_reevaluate;
+pub use self::report::handle_report;
 pub use self::webhook::handle_webhook;
 
 pub struct ServerState {
     api_client: GithubClient,
     webhook_secret: String,
+    /// Server-wide config. Repositories can override parts of it with their own config file.
+    config: ConfigSource,
+    /// The server-wide config on its own, for when the config of a repository cannot be loaded.
+    server_config: Config,
     /// Store auth tokens for different installations. Tokens expire once in a while, but can be
     /// regenerated using the private key stored in GithubClient.
     auth_tokens: RwLock<TokenStore>,
+    /// Latest report for each judged commit, so users can see every problem and not only the
+    /// first one that fits in the status.
+    reports: RwLock<ReportStore>,
+    /// Where approvals that bypassed rules are recorded, if anywhere.
+    audit_log: Option<AuditLog>,
+    /// Token for the admin endpoints. They are disabled without one.
+    admin_token: Option<String>,
+    /// Repositories whose open PRs are being judged again in the background.
+    reevaluations: Mutex<ReevaluationQueue>,
 }
 
 impl ServerState {
-    pub fn new(api_client: GithubClient, webhook_secret: &str) -> Self {
+    pub fn new(
+        api_client: GithubClient,
+        webhook_secret: &str,
+        config: ConfigSource,
+        server_config: Config,
+        audit_log: Option<AuditLog>,
+        admin_token: Option<String>,
+    ) -> Self {
         ServerState {
             api_client: api_client,
             webhook_secret: webhook_secret.to_owned(),
+            config,
+            server_config,
             auth_tokens: RwLock::new(TokenStore::default()),
+            reports: RwLock::new(ReportStore::default()),
+            audit_log,
+            admin_token,
+            reevaluations: Mutex::new(ReevaluationQueue::default()),
         }
     }
 
@@ -60,9 +96,75 @@ impl ServerState {
         tokens.get_token(installation_id)
     }
 
+    /// Forget everything about an installation of the app: its auth token and the reports for its
+    /// repositories.
+    fn remove_installation(&self, installation_id: u64, repositories: &[String]) {
+        if let Ok(mut tokens) = self.auth_tokens.write() {
+            tokens.remove_token(installation_id);
+        }
+        self.remove_repositories(repositories);
+    }
+
+    fn remove_repositories(&self, repositories: &[String]) {
+        if let Ok(mut reports) = self.reports.write() {
+            for repository in repositories {
+                reports.remove_repository(repository);
+            }
+        }
+    }
+
+    fn add_report(&self, report: Report) {
+        if let Ok(mut reports) = self.reports.write() {
+            reports.add_report(report);
+        }
+    }
+
+    fn get_report(&self, repository: &str, sha: &str) -> Option<Report> {
+        let reports = self.reports.read().ok()?;
+        reports.get_report(repository, sha)
+    }
+
+    /// Returns `true` if the open PRs in a repository should be judged again now, or `false` if
+    /// it is already being done and another run was queued.
+    fn start_reevaluation(&self, repository: &str, base_branch: Option<String>) -> bool {
+        let mut reevaluations = self.reevaluations.lock().unwrap();
+        reevaluations.start(repository, base_branch)
+    }
+
+    /// Returns the run that was queued while the open PRs in a repository were being judged.
+    fn finish_reevaluation(&self, repository: &str) -> Option<Rerun> {
+        let mut reevaluations = self.reevaluations.lock().unwrap();
+        reevaluations.finish(repository)
+    }
+
+    fn audit(&self, record: &AuditRecord) {
+        if let Some(ref audit_log) = self.audit_log {
+            info!(
+                "Recording approval of PR #{} in {} in the audit log",
+                record.number, record.repository
+            );
+            log_error_trace_if_err(&audit_log.append(record));
+        }
+    }
+
     fn webhook_secret(&self) -> &str {
         &self.webhook_secret
     }
+
+    fn admin_token(&self) -> Option<&str> {
+        self.admin_token
+            .as_ref()
+            .map(String::as_str)
+            .filter(|token| !token.is_empty())
+    }
+
+    fn config(&self) -> &ConfigSource {
+        &self.config
+    }
+
+    fn server_config(&self) -> &Config {
+        &self.server_config
+    }
 }
 
 impl actix_web::ResponseError for EventError {
@@ -0,0 +1,225 @@
+use actix_web::HttpResponse;
+use crypto::util::fixed_time_eq;
+use failure::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::judge::judge_pull_request;
+use super::prelude::*;
+use github_api;
+use reevaluation_queue::Rerun;
+use utils::log_error_trace;
+
+/// Time to wait between judging PRs, so a large repository doesn't use up the rate limit at once.
+const DELAY_BETWEEN_PRS: Duration = Duration::from_secs(1);
+
+/// Wait for the rate limit to reset when fewer requests than this remain. Judging a single PR
+/// takes a handful of requests.
+const MIN_REMAINING_REQUESTS: u64 = 100;
+
+/// Judge every open PR in some repositories again, in the background. The repositories are done
+/// one at a time.
+///
+/// Only PRs against `base_branch` are judged if it is set, since the config is read from the base
+/// branch of each PR.
+///
+/// Repositories that are already being judged are skipped here. They are judged once more when
+/// the current run is done instead, however many times that is requested meanwhile.
+pub fn spawn(
+    state: Arc<ServerState>,
+    installation_id: u64,
+    repositories: Vec<String>,
+    base_branch: Option<String>,
+) {
+    let repositories: Vec<String> = repositories
+        .into_iter()
+        .filter(|full_name| {
+            let start = state.start_reevaluation(full_name, base_branch.clone());
+            if !start {
+                info!("Already judging open PRs in {}. Judging them again after.", full_name);
+            }
+            start
+        }).collect();
+    if repositories.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        for full_name in &repositories {
+            judge_until_done(
+                full_name,
+                base_branch.clone(),
+                |branch| match reevaluate(&state, installation_id, full_name, branch) {
+                    Ok(count) => info!("Judged {} open PRs in {} again", count, full_name),
+                    Err(error) => {
+                        error!("Could not judge open PRs in {} again", full_name);
+                        log_error_trace(error.as_fail());
+                    }
+                },
+                || state.finish_reevaluation(full_name),
+            );
+        }
+    });
+}
+
+/// Run `judge` for a repository, and again for every run that `finish` says was queued meanwhile.
+/// A panic only ends the run it happened in, so `finish` is always called and the repository is
+/// never left marked as being judged.
+fn judge_until_done<J, F>(full_name: &str, mut base_branch: Option<String>, mut judge: J, finish: F)
+where
+    J: FnMut(Option<&str>),
+    F: Fn() -> Option<Rerun>,
+{
+    loop {
+        let branch = base_branch.as_ref().map(String::as_str);
+        if panic::catch_unwind(AssertUnwindSafe(|| judge(branch))).is_err() {
+            error!("Judging open PRs in {} again panicked", full_name);
+        }
+
+        match finish() {
+            Some(rerun) => base_branch = rerun.base_branch,
+            None => break,
+        }
+    }
+}
+
+fn reevaluate(
+    state: &ServerState,
+    installation_id: u64,
+    full_name: &str,
+    base_branch: Option<&str>,
+) -> Result<usize, Error> {
+    let repo_url = github_api::repo_url(full_name);
+    let auth_token = state.get_or_create_auth_token(installation_id)?;
+    let repository = state.api_client.get_repository(&auth_token, &repo_url)?;
+    let pull_requests = state
+        .api_client
+        .list_open_pull_requests(&auth_token, &repo_url)?;
+
+    let mut count = 0;
+    for pr in &pull_requests {
+        let pr_base = pr.base.ref_name.as_ref().map(String::as_str);
+        if base_branch.map_or(false, |branch| pr_base != Some(branch)) {
+            continue;
+        }
+
+        // Tokens expire, and a large repository can take a while.
+        let auth_token = state.get_or_create_auth_token(installation_id)?;
+        wait_for_rate_limit(state, &auth_token)?;
+
+        debug!("Judging PR #{} in {} again", pr.number, full_name);
+        judge_pull_request(state, &auth_token, &repo_url, &repository, pr, None, false);
+        count += 1;
+
+        thread::sleep(DELAY_BETWEEN_PRS);
+    }
+
+    Ok(count)
+}
+
+fn wait_for_rate_limit(state: &ServerState, auth_token: &str) -> Result<(), Error> {
+    let rate_limit = state.api_client.get_rate_limit(auth_token)?;
+    if rate_limit.remaining >= MIN_REMAINING_REQUESTS {
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let wait = Duration::from_secs(rate_limit.reset.saturating_sub(now) + 1);
+    warn!(
+        "Only {} of {} API requests left. Waiting {} seconds for the rate limit to reset.",
+        rate_limit.remaining,
+        rate_limit.limit,
+        wait.as_secs()
+    );
+    thread::sleep(wait);
+    Ok(())
+}
+
+/// Judge every open PR in a repository again, like after changing the server config. Needs the
+/// admin token.
+pub fn handle_reevaluate(req: &HttpRequest<Arc<ServerState>>) -> HttpResponse {
+    let state = req.state();
+    let admin_token = match state.admin_token() {
+        Some(token) => token,
+        None => return HttpResponse::NotFound().finish(),
+    };
+
+    let authorized = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("Bearer "))
+        .map(|value| &value["Bearer ".len()..])
+        .map_or(false, |token| fixed_time_eq(token.as_bytes(), admin_token.as_bytes()));
+    if !authorized {
+        warn!("Rejected re-evaluation request with a missing or wrong admin token");
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let params = req.match_info();
+    let full_name = format!(
+        "{}/{}",
+        params.get("owner").unwrap_or_default(),
+        params.get("repo").unwrap_or_default()
+    );
+
+    let installation = match state.api_client.get_repository_installation(&full_name) {
+        Ok(installation) => installation,
+        Err(error) => {
+            log_error_trace(&error);
+            return HttpResponse::NotFound()
+                .content_type("text/plain; charset=utf-8")
+                .body(format!("prgnome is not installed on {}.", full_name));
+        }
+    };
+
+    info!("Judging open PRs in {} again, as requested", full_name);
+    spawn(Arc::clone(state), installation.id, vec![full_name.clone()], None);
+
+    HttpResponse::Accepted()
+        .content_type("text/plain; charset=utf-8")
+        .body(format!("Judging open PRs in {} again.", full_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reevaluation_queue::ReevaluationQueue;
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+
+    mod judge_until_done {
+        use super::*;
+
+        #[test]
+        fn it_releases_the_repository_when_judging_panics() {
+            let queue = Mutex::new(ReevaluationQueue::default());
+            queue.lock().unwrap().start("example/repo", None);
+            let runs = RefCell::new(Vec::new());
+
+            judge_until_done(
+                "example/repo",
+                None,
+                |branch| {
+                    runs.borrow_mut().push(branch.map(String::from));
+                    if runs.borrow().len() == 1 {
+                        // Queued while the first run is going on.
+                        queue
+                            .lock()
+                            .unwrap()
+                            .start("example/repo", Some(String::from("master")));
+                        panic!("Failed to judge a PR");
+                    }
+                },
+                || queue.lock().unwrap().finish("example/repo"),
+            );
+
+            assert_eq!(*runs.borrow(), vec![None, Some(String::from("master"))]);
+            assert!(queue.lock().unwrap().start("example/repo", None));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use actix_web::HttpResponse;
+
+use super::prelude::*;
+use judgement::{Judgement, Violation};
+use report_store::Report;
+
+/// Show the latest report for a commit, as HTML or as JSON depending on the Accept header.
+pub fn handle_report(req: &HttpRequest<Arc<ServerState>>) -> HttpResponse {
+    let params = req.match_info();
+    let repository = format!(
+        "{}/{}",
+        params.get("owner").unwrap_or_default(),
+        params.get("repo").unwrap_or_default()
+    );
+    let sha = params.get("sha").unwrap_or_default();
+
+    let report = match req.state().get_report(&repository, sha) {
+        Some(report) => report,
+        None => {
+            return HttpResponse::NotFound()
+                .content_type("text/plain; charset=utf-8")
+                .body(format!("No report for {} in {}.", sha, repository));
+        }
+    };
+
+    if wants_json(req) {
+        HttpResponse::Ok().json(report)
+    } else {
+        HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(render_html(&report))
+    }
+}
+
+fn wants_json<S>(req: &HttpRequest<S>) -> bool {
+    req.headers()
+        .get("Accept")
+        .and_then(|value| value.to_str().ok())
+        .map(|accept| accept.contains("application/json"))
+        .unwrap_or(false)
+}
+
+fn render_violations(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|violation| {
+            format!(
+                "<li><strong>{}</strong><p>{}</p></li>",
+                escape_html(&violation.message),
+                escape_html(&violation.hint)
+            )
+        }).collect()
+}
+
+fn render_html(report: &Report) -> String {
+    let summary = match report.judgement {
+        Judgement::Approved => String::from("<p>No problems found. This PR can be merged.</p>"),
+        Judgement::ForceApproved(ref reason) => format!(
+            "<p>Approved regardless of any problems: {}</p>",
+            escape_html(reason)
+        ),
+        Judgement::Overridden {
+            ref labels,
+            ref waived,
+        } => format!(
+            "<p>Approved because these problems were waived with {}:</p><ol>{}</ol>",
+            escape_html(&labels.join(", ")),
+            render_violations(waived)
+        ),
+        Judgement::NotApproved { ref violations } => format!(
+            "<p>Fix these problems before merging this PR:</p><ol>{}</ol>",
+            render_violations(violations)
+        ),
+    };
+
+    format!(
+        "<!DOCTYPE html>\
+         <html>\
+         <head><meta charset=\"utf-8\"><title>prgnome: {repository}#{number}</title></head>\
+         <body>\
+         <h1>{repository}#{number}</h1>\
+         <p>Judgement of commit <code>{sha}</code>.</p>\
+         {summary}\
+         </body>\
+         </html>",
+        repository = escape_html(&report.repository),
+        number = report.number,
+        sha = escape_html(&report.sha),
+        summary = summary,
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                other => escaped.push(other),
+            }
+            escaped
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_escapes_html() {
+        assert_eq!(
+            escape_html("<b>\"fixup!\" & 'tmp'</b>"),
+            "&lt;b&gt;&quot;fixup!&quot; &amp; &#39;tmp&#39;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn it_lists_every_violation() {
+        let report = Report {
+            repository: String::from("example/repo"),
+            number: 12,
+            sha: String::from("abc123"),
+            judgement: Judgement::NotApproved {
+                violations: vec![
+                    Violation {
+                        rule: String::from("forbidden_intros"),
+                        message: String::from("Rebase away \"fixup! <html>\""),
+                        hint: String::from("Use git rebase -i --autosquash"),
+                    },
+                    Violation {
+                        rule: String::from("forbidden_labels"),
+                        message: String::from("Remove the \"wip\" label"),
+                        hint: String::from("Remove the label when ready"),
+                    },
+                ],
+            },
+        };
+
+        let html = render_html(&report);
+        assert!(html.contains("<h1>example/repo#12</h1>"));
+        assert!(html.contains("<code>abc123</code>"));
+        assert!(html.contains(
+            "<li><strong>Rebase away &quot;fixup! &lt;html&gt;&quot;</strong>\
+             <p>Use git rebase -i --autosquash</p></li>"
+        ));
+        assert!(html.contains("<strong>Remove the &quot;wip&quot; label</strong>"));
+    }
+}
@@ -1,23 +1,27 @@
+use actix_web::HttpResponse;
 use crypto::hmac::Hmac;
 use crypto::mac::{Mac, MacResult};
 use crypto::sha1::Sha1;
 use hex::FromHex;
 use std::fmt;
 
+use super::commands::run_commands;
+use super::judge::judge_pull_request;
 use super::prelude::*;
-use event::Event;
-use github_api::{Client as GithubClient, NewStatus, State as CommitState};
-use judgement::*;
-use utils::{log_error_trace, log_error_trace_if_err};
-
-const STATUS_CONTEXT_NAME: &str = "mange/prgnome";
+use super::reevaluate;
+use config::REPO_CONFIG_PATH;
+use event::{
+    Event, InstallationEvent, InstallationRepositoriesEvent, InstalledRepository,
+    IssueCommentEvent, PingEvent, PullRequestEvent,
+};
+use utils::log_error_trace_if_err;
 
 pub fn handle_webhook(
     state: State<Arc<ServerState>>,
     event_name: EventName,
     signature: GithubSignature,
     body: String,
-) -> Result<String> {
+) -> Result<HttpResponse> {
     debug!("Got webhook request of type {}", event_name);
 
     if !verify_signature(&body, &signature, state.webhook_secret()) {
@@ -30,8 +34,9 @@ pub fn handle_webhook(
     let event = Event::parse_json(&event_name.0, &body)?;
     match event {
         Event::PullRequest(pr_event) => {
-            if let (Some(repo_url), Some(pr), Some(installation)) = (
+            if let (Some(repo_url), Some(repository), Some(pr), Some(installation)) = (
                 pr_event.repo_url(),
+                pr_event.repository(),
                 pr_event.pull_request(),
                 pr_event.installation(),
             ) {
@@ -44,89 +49,149 @@ pub fn handle_webhook(
                     }
                 };
 
-                let label_names: Vec<&str> =
-                    pr.labels.iter().map(|label| label.name.as_str()).collect();
-
-                debug!("Loading commits in PR range");
-                let (total_commits, commit_messages) = load_commits(
-                    &state.api_client,
-                    repo_url,
-                    &auth_token,
-                    &pr.base.sha,
-                    &pr.head.sha,
-                ).unwrap_or_else(|err| {
-                    log_error_trace(err.as_fail());
-                    Default::default()
-                });
-
-                let intel = Intel {
-                    label_names,
-                    total_commits,
-                    commit_messages,
-                };
-
-                let judgement = intel.validate();
-                debug!("Judgement: {:?}", judgement);
-                let new_status = new_status_from_judgement(judgement);
-                info!("Setting new status to: {:#?}", new_status);
-
-                log_error_trace_if_err(&state.api_client.create_status(
+                judge_pull_request(
+                    &state,
                     &auth_token,
                     repo_url,
-                    &pr.head.sha,
-                    new_status,
-                ));
+                    repository,
+                    pr,
+                    pr_event.applied_label(),
+                    is_new_head(&pr_event),
+                );
             } else {
                 info!("Unsupported PR webhook event");
                 debug!("{:#?}", pr_event);
             }
         }
+        Event::Push(push_event) => {
+            if let Some(ref installation) = push_event.installation {
+                if push_event.is_to_default_branch() && push_event.touches(REPO_CONFIG_PATH) {
+                    info!(
+                        "Config of {} changed. Judging its open PRs again.",
+                        push_event.repository.full_name
+                    );
+                    reevaluate::spawn(
+                        Arc::clone(&state),
+                        installation.id,
+                        vec![push_event.repository.full_name.clone()],
+                        push_event.branch().map(String::from),
+                    );
+                }
+            }
+        }
+        Event::IssueComment(IssueCommentEvent::Created {
+            issue,
+            comment,
+            repository,
+            installation,
+        }) => {
+            let auth_token = state.get_or_create_auth_token(installation.id)?;
+            log_error_trace_if_err(&run_commands(
+                &state,
+                &auth_token,
+                &repository,
+                &issue,
+                &comment,
+            ));
+        }
+        Event::Ping(ping_event) => return Ok(HttpResponse::Ok().json(check_ping(&ping_event))),
+        Event::Installation(InstallationEvent::Created {
+            installation,
+            repositories,
+        }) => {
+            info!("Installed on {} repositories", repositories.len());
+            reevaluate::spawn(
+                Arc::clone(&state),
+                installation.id,
+                full_names(&repositories),
+                None,
+            );
+        }
+        Event::Installation(InstallationEvent::Deleted {
+            installation,
+            repositories,
+        }) => {
+            info!("Uninstalled from installation {}", installation.id);
+            state.remove_installation(installation.id, &full_names(&repositories));
+        }
+        Event::InstallationRepositories(InstallationRepositoriesEvent::Added {
+            installation,
+            repositories_added,
+        }) => {
+            info!("Added to {} repositories", repositories_added.len());
+            reevaluate::spawn(
+                Arc::clone(&state),
+                installation.id,
+                full_names(&repositories_added),
+                None,
+            );
+        }
+        Event::InstallationRepositories(InstallationRepositoriesEvent::Removed {
+            repositories_removed,
+            ..
+        }) => {
+            info!("Removed from {} repositories", repositories_removed.len());
+            state.remove_repositories(&full_names(&repositories_removed));
+        }
         _ => {}
     }
 
-    Ok(format!("OK"))
+    Ok(HttpResponse::Ok().body("OK"))
 }
 
-fn load_commits(
-    api_client: &GithubClient,
-    repo_url: &str,
-    auth_token: &str,
-    base_sha: &str,
-    head_sha: &str,
-) -> Result<(u64, Vec<String>)> {
-    let commit_list = api_client.list_commits_in_range(auth_token, repo_url, base_sha, head_sha)?;
-    Ok((
-        commit_list.total_commits,
-        commit_list
-            .commits
-            .into_iter()
-            .map(|c| c.commit.message)
-            .collect(),
-    ))
+/// Events that the app should be subscribed to, and what happens without them.
+const NEEDED_EVENTS: &[(&str, &str)] = &[
+    ("pull_request", "PRs will not be judged"),
+    ("push", "open PRs will not be judged again when the config changes"),
+    ("issue_comment", "slash commands in PR comments will not work"),
+];
+
+/// Summary of the webhook setup, sent back on ping events.
+#[derive(Debug, Serialize)]
+struct PingSummary<'a> {
+    zen: &'a str,
+    hook_id: u64,
+    events: &'a [String],
+    missing_events: Vec<&'static str>,
 }
 
-fn new_status_from_judgement(judgement: Judgement) -> NewStatus {
-    let (state, description) = match judgement {
-        Judgement::Approved => (CommitState::Success, None),
-        Judgement::ForceApproved(reason) => (CommitState::Success, Some(reason)),
-        Judgement::NotApproved {
-            main_problem,
-            total_violations,
-        } => {
-            let message = if total_violations == 1 {
-                main_problem
-            } else {
-                format!("{} problems. First one: {}", total_violations, main_problem)
-            };
-            (CommitState::Failure, Some(message))
+fn check_ping(ping_event: &PingEvent) -> PingSummary<'_> {
+    info!("Got ping for hook {}: {}", ping_event.hook_id, ping_event.zen);
+
+    let mut missing_events = Vec::new();
+    for &(event, consequence) in NEEDED_EVENTS {
+        if !ping_event.is_subscribed_to(event) {
+            warn!(
+                "The app is not subscribed to {} events, so {}. Subscribe to them in the app \
+                 settings on Github.",
+                event, consequence
+            );
+            missing_events.push(event);
         }
-    };
+    }
+
+    PingSummary {
+        zen: &ping_event.zen,
+        hook_id: ping_event.hook_id,
+        events: &ping_event.hook.events,
+        missing_events,
+    }
+}
+
+fn full_names(repositories: &[InstalledRepository]) -> Vec<String> {
+    repositories
+        .iter()
+        .map(|repository| repository.full_name.clone())
+        .collect()
+}
 
-    NewStatus {
-        state: state,
-        description,
-        context: STATUS_CONTEXT_NAME.into(),
-        target_url: None,
+/// Whether the event means that the PR has a new head commit that has not been judged yet.
+fn is_new_head(pr_event: &PullRequestEvent) -> bool {
+    match pr_event {
+        PullRequestEvent::Opened { .. }
+        | PullRequestEvent::Reopened { .. }
+        | PullRequestEvent::Synchronize { .. } => true,
+        _ => false,
     }
 }
 
@@ -217,56 +282,13 @@ impl std::ops::Deref for GithubSignature {
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    mod new_status_from_judgement {
-        use super::*;
-
-        #[test]
-        fn it_returns_success_on_approved_judgement() {
-            let judgement = Judgement::Approved;
-            let new_state = new_status_from_judgement(judgement);
-
-            assert_eq!(new_state.state, CommitState::Success);
-            assert_eq!(new_state.description, None);
-        }
-
-        #[test]
-        fn it_returns_failure_on_not_approved_judgement_with_single_problem() {
-            let judgement = Judgement::NotApproved {
-                main_problem: String::from("Not cool enough"),
-                total_violations: 1,
-            };
-            let new_state = new_status_from_judgement(judgement);
-
-            assert_eq!(new_state.state, CommitState::Failure);
-            assert_eq!(new_state.description, Some(String::from("Not cool enough")));
-        }
-
-        #[test]
-        fn it_returns_failure_on_not_approved_judgement_with_multiple_problems() {
-            let judgement = Judgement::NotApproved {
-                main_problem: String::from("Not cool enough"),
-                total_violations: 4,
-            };
-            let new_state = new_status_from_judgement(judgement);
-
-            assert_eq!(new_state.state, CommitState::Failure);
-            assert_eq!(
-                new_state.description,
-                Some(String::from("4 problems. First one: Not cool enough")),
-            );
-        }
-
-        #[test]
-        fn it_returns_success_on_force_approved_judgement() {
-            let judgement = Judgement::ForceApproved(String::from("Tagged with something cool"));
-            let new_state = new_status_from_judgement(judgement);
-
-            assert_eq!(new_state.state, CommitState::Success);
-            assert_eq!(
-                new_state.description,
-                Some(String::from("Tagged with something cool")),
-            );
-        }
+    use event::pr_event;
+
+    #[test]
+    fn it_treats_opened_and_synchronized_prs_as_new_heads() {
+        assert!(is_new_head(&pr_event("webhook_pr_opened.json")));
+        assert!(is_new_head(&pr_event("webhook_pr_synchronized.json")));
+        assert!(!is_new_head(&pr_event("webhook_pr_labeled.json")));
+        assert!(!is_new_head(&pr_event("webhook_pr_edited.json")));
     }
 }
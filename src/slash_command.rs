@@ -0,0 +1,89 @@
+use judgement::is_rule_name;
+
+const PREFIX: &str = "/prgnome";
+
+/// A command given to prgnome in a PR comment, like `/prgnome recheck`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlashCommand {
+    /// Judge the PR again.
+    Recheck,
+    /// Reply with every problem and how to fix it.
+    Explain,
+    /// Waive a rule on the PR.
+    Ignore(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Fail)]
+pub enum SlashCommandError {
+    #[fail(display = "Use one of \"recheck\", \"explain\", or \"ignore <rule>\"")]
+    MissingCommand,
+
+    #[fail(display = "Unknown command \"{}\"", _0)]
+    UnknownCommand(String),
+
+    #[fail(display = "Name the rule to ignore, like \"/prgnome ignore max_commits\"")]
+    MissingRule,
+
+    #[fail(display = "Unknown rule \"{}\"", _0)]
+    UnknownRule(String),
+}
+
+/// Find the commands in a comment. Every command is on its own line, starting with `/prgnome`.
+pub fn parse(body: &str) -> Vec<Result<SlashCommand, SlashCommandError>> {
+    body.lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            if words.next() != Some(PREFIX) {
+                return None;
+            }
+
+            Some(match (words.next(), words.next()) {
+                (None, _) => Err(SlashCommandError::MissingCommand),
+                (Some("recheck"), _) => Ok(SlashCommand::Recheck),
+                (Some("explain"), _) => Ok(SlashCommand::Explain),
+                (Some("ignore"), None) => Err(SlashCommandError::MissingRule),
+                (Some("ignore"), Some(rule)) if is_rule_name(rule) => {
+                    Ok(SlashCommand::Ignore(rule.to_owned()))
+                }
+                (Some("ignore"), Some(rule)) => Err(SlashCommandError::UnknownRule(rule.into())),
+                (Some(command), _) => Err(SlashCommandError::UnknownCommand(command.into())),
+            })
+        }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_commands_on_their_own_lines() {
+        assert_eq!(
+            parse("Looks good to me.\n\n/prgnome recheck\r\n  /prgnome   explain\n"),
+            vec![Ok(SlashCommand::Recheck), Ok(SlashCommand::Explain)]
+        );
+        assert_eq!(
+            parse("/prgnome ignore max_commits"),
+            vec![Ok(SlashCommand::Ignore(String::from("max_commits")))]
+        );
+    }
+
+    #[test]
+    fn it_ignores_comments_without_commands() {
+        assert_eq!(parse("Run /prgnome recheck when CI is done"), vec![]);
+        assert_eq!(parse("/prgnomes recheck"), vec![]);
+        assert_eq!(parse(""), vec![]);
+    }
+
+    #[test]
+    fn it_reports_invalid_commands() {
+        assert_eq!(
+            parse("/prgnome\n/prgnome merge\n/prgnome ignore\n/prgnome ignore everything"),
+            vec![
+                Err(SlashCommandError::MissingCommand),
+                Err(SlashCommandError::UnknownCommand(String::from("merge"))),
+                Err(SlashCommandError::MissingRule),
+                Err(SlashCommandError::UnknownRule(String::from("everything"))),
+            ]
+        );
+    }
+}
@@ -14,6 +14,10 @@ impl TokenStore {
             .insert(installation_id, (Instant::now(), token.into()));
     }
 
+    pub fn remove_token(&mut self, installation_id: u64) {
+        self.tokens.remove(&installation_id);
+    }
+
     pub fn get_token(&self, installation_id: u64) -> Option<String> {
         match self.tokens.get(&installation_id) {
             Some((time, token)) if time.elapsed() < Duration::from_secs(MAX_TTL) => {
@@ -41,6 +45,16 @@ mod tests {
         assert_eq!(store.get_token(55), None);
     }
 
+    #[test]
+    fn it_removes_tokens() {
+        let mut store = TokenStore::default();
+        store.add_token(12, "foobar");
+        store.add_token(55, "bazqux");
+        store.remove_token(12);
+        assert_eq!(store.get_token(12), None);
+        assert_eq!(store.get_token(55), Some(String::from("bazqux")));
+    }
+
     #[test]
     fn it_does_not_retrieve_old_tokens() {
         let mut store = TokenStore::default();